@@ -0,0 +1,154 @@
+/// Plain-text formatting for cards and hands, shared by every frontend.
+use std::fmt;
+
+use crate::model::*;
+
+impl From<Rank> for String {
+    fn from(r: Rank) -> String {
+        let c = match r {
+            Rank::Ace => "A",
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+        };
+        c.to_owned()
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s: String = (*self).into();
+        write!(f, "{: >2}", s)
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = match self {
+            Suit::Club => '♣',
+            Suit::Diamond => '♦',
+            Suit::Heart => '♥',
+            Suit::Spade => '♠',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Card(r, s) = self;
+        write!(f, "{: >2}{}", r, s)
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Hand::Pair => "Pair",
+            Hand::ThreeOfAKind => "Three of a Kind",
+            Hand::StraightThree => "Three-Card Straight",
+            Hand::FourOfAKind => "Four of a Kind",
+            Hand::FullHouse => "Full House",
+            Hand::StraightFive => "Five-Card Straight",
+            Hand::Flush => "Flush",
+            Hand::StraightFlush => "Straight Flush!",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// Positions are named by row then column, e.g. `tl` for top left.
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Position(rowid, colid) = self;
+        let r = match rowid {
+            RowId::Top => 't',
+            RowId::Middle => 'm',
+            RowId::Bottom => 'b',
+        };
+        let c = match colid {
+            ColumnId::Left => 'l',
+            ColumnId::Center => 'c',
+            ColumnId::Right => 'r',
+        };
+        write!(f, "{}{}", r, c)
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "The game is over"),
+            GameError::EmptyStack(p) => write!(f, "Stack {} is empty", p),
+            GameError::NothingSelected => write!(f, "Nothing is selected"),
+            GameError::NoTrashes => write!(f, "There are no trashes left"),
+            GameError::NotAHand(reason) => write!(f, "{}", reason),
+            GameError::NotThatHand(h) => write!(f, "The selected cards don't make a {}", h),
+            GameError::NothingToUndo => write!(f, "There's nothing to undo"),
+            GameError::UndoLimit(n) => write!(f, "The limit of {} undos has been reached", n),
+            GameError::NothingToRedo => write!(f, "There's nothing to redo"),
+            GameError::Overdraw { wanted, left } => write!(
+                f,
+                "The rules deal {} cards when only {} are left in the deck",
+                wanted, left
+            ),
+            GameError::Underdeal(n) => write!(
+                f,
+                "The rules leave {} cards undealt besides the bonus card",
+                n
+            ),
+            GameError::BadDeal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl fmt::Display for Unplayable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unplayable::EmptyStack(p) => write!(f, "Stack {} is empty", p),
+            Unplayable::NoTrashes => write!(f, "There are no trashes left"),
+            Unplayable::WrongCount(n) => write!(f, "No hand has {} cards", n),
+            Unplayable::RanksDiffer => write!(f, "The ranks don't match"),
+            Unplayable::NotConsecutive => write!(f, "The ranks aren't the same or in a row"),
+            Unplayable::WrapsAround => write!(f, "Straights can't wrap around, as in K-A-2"),
+            Unplayable::NoFiveCardHand => {
+                write!(f, "Five cards need a straight, flush or full house")
+            }
+            Unplayable::TooFewRows { spanned: 1, .. } => write!(f, "All the cards are in one row"),
+            Unplayable::TooFewRows { needed, .. } => {
+                write!(f, "A hand needs cards from {} rows", needed)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Unaccounted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = |cards: &[Card]| -> String {
+            let names: Vec<String> = cards
+                .iter()
+                .map(|c| c.to_string().trim().to_owned())
+                .collect();
+            names.join(" ")
+        };
+        match (self.missing.is_empty(), self.duplicated.is_empty()) {
+            (false, true) => write!(f, "Missing {}", names(&self.missing)),
+            (true, false) => write!(f, "More than one {}", names(&self.duplicated)),
+            _ => write!(
+                f,
+                "Missing {}, and more than one {}",
+                names(&self.missing),
+                names(&self.duplicated)
+            ),
+        }
+    }
+}
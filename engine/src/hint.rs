@@ -0,0 +1,98 @@
+/// Suggestions for the player's next move.
+use std::collections::HashSet;
+
+use crate::model::*;
+use crate::strategy::{Greedy, Strategy, View};
+
+/// The move to suggest, if there is one.
+pub fn suggest(game: &Game) -> Option<(Move, HashSet<Position>)> {
+    Greedy.choose(&View::of(game))
+}
+
+/// Why a move is worth making: what it scores and which piles it clears.
+pub fn explain(game: &Game, mv: Move, positions: &HashSet<Position>) -> String {
+    let preview = game.preview(mv, positions);
+    let mut reasons = match mv {
+        Move::Trash(pos) => format!("Trash {}", pos),
+        Move::PlayHand(h) if preview.multiplier > 1 => {
+            let Card(_, bonus_suit) = game.bonus_card;
+            format!(
+                "{} for {} pts, {} by the {} bonus.",
+                h,
+                preview.base,
+                multiplied(preview.base, preview.multiplier),
+                bonus_suit
+            )
+        }
+        Move::PlayHand(h) => format!("{} for {} pts.", h, preview.base),
+    };
+    let cleared: Vec<String> = preview
+        .cleared
+        .iter()
+        .map(|(pos, bonus)| format!("{} (+{})", pos, bonus))
+        .collect();
+    match (mv, preview.cleared.first()) {
+        (Move::Trash(_), None) => reasons.push_str(" to uncover the next card."),
+        (Move::Trash(_), Some((_, bonus))) => {
+            reasons.push_str(&format!(" to clear it (+{}).", bonus))
+        }
+        (Move::PlayHand(_), None) => (),
+        (Move::PlayHand(_), Some(_)) => {
+            reasons.push_str(&format!(" Clears {}.", cleared.join(", ")))
+        }
+    }
+    reasons
+}
+
+fn multiplied(points: u32, multiplier: u32) -> String {
+    match multiplier {
+        2 => format!("doubled to {}", points * 2),
+        _ => format!("x{} to {}", multiplier, points * multiplier),
+    }
+}
+
+#[test]
+fn test_explain() {
+    let tl = Position(RowId::Top, ColumnId::Left);
+    let bl = Position(RowId::Bottom, ColumnId::Left);
+    let mut g = Game::generate_with_seed(0);
+    g.spread = Spread::empty();
+    g.spread.tl = vec![Card(Rank::Four, Suit::Club), Card(Rank::Two, Suit::Heart)];
+    g.spread.bl = vec![Card(Rank::Two, Suit::Spade)];
+    g.bonus_card = Card(Rank::King, Suit::Heart);
+    let positions: HashSet<Position> = [tl, bl].iter().copied().collect();
+    assert_eq!(
+        explain(&g, Move::PlayHand(Hand::Pair), &positions),
+        "Pair for 1 pts, doubled to 2 by the ♥ bonus. Clears bl (+5)."
+    );
+    let only_tl: HashSet<Position> = [tl].iter().copied().collect();
+    assert_eq!(
+        explain(&g, Move::Trash(tl), &only_tl),
+        "Trash tl to uncover the next card."
+    );
+    let only_bl: HashSet<Position> = [bl].iter().copied().collect();
+    assert_eq!(
+        explain(&g, Move::Trash(bl), &only_bl),
+        "Trash bl to clear it (+5)."
+    );
+}
+
+#[test]
+fn test_hint_leaves_selection_alone() {
+    use crate::update::update;
+    let mut g = Game::generate_with_seed(5);
+    update(Msg::Hint, &mut g).unwrap();
+    let (_, positions) = g.hint.clone().expect("A new deal always has a move");
+    assert!(g.selected.is_empty());
+    update(Msg::Hint, &mut g).unwrap();
+    assert_eq!(g.selected, positions);
+    assert_eq!(g.hint, None);
+    // A message that's turned down leaves the hint showing
+    update(Msg::Hint, &mut g).unwrap();
+    assert_eq!(update(Msg::Undo, &mut g), Err(GameError::NothingToUndo));
+    assert_eq!(g.hint.as_ref().map(|(_, p)| p), Some(&positions));
+    update(Msg::Redo, &mut g).unwrap_err();
+    assert!(g.hint.is_some());
+    update(Msg::NewGame, &mut g).unwrap();
+    assert_eq!(g.hint, None);
+}
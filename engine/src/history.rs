@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Undo and redo stacks of past game states, most recent last.
+///
+/// `limit` caps how many undos may be spent over the course of one game;
+/// `None` allows any number and `Some(0)` disables undo entirely.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    undos_used: u32,
+    limit: Option<u32>,
+}
+
+impl<T> History<T> {
+    pub fn new(limit: Option<u32>) -> Self {
+        History {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            undos_used: 0,
+            limit,
+        }
+    }
+
+    /// Remember a state before changing it. Anything that could be redone is
+    /// forgotten.
+    pub fn record(&mut self, state: T) {
+        self.undo.push(state);
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        let within_limit = match self.limit {
+            Some(limit) => self.undos_used < limit,
+            None => true,
+        };
+        within_limit && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Trade the current state for the one before it.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        if !self.can_undo() {
+            return None;
+        }
+        let prev = self.undo.pop()?;
+        self.redo.push(current);
+        self.undos_used += 1;
+        Some(prev)
+    }
+
+    /// Trade the current state for the one that was last undone.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub fn undos_used(&self) -> u32 {
+        self.undos_used
+    }
+
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Option<u32>) {
+        self.limit = limit;
+    }
+
+    /// Forget every saved state and reset the undo counter, keeping the limit.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.undos_used = 0;
+    }
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut h = History::new(None);
+    h.record(1);
+    h.record(2);
+    assert_eq!(h.undo(3), Some(2));
+    assert_eq!(h.undo(2), Some(1));
+    assert_eq!(h.undo(1), None);
+    assert_eq!(h.redo(1), Some(2));
+    assert_eq!(h.redo(2), Some(3));
+    assert_eq!(h.redo(3), None);
+    assert_eq!(h.undos_used(), 2);
+}
+
+#[test]
+fn test_record_clears_redo() {
+    let mut h = History::new(None);
+    h.record(1);
+    assert_eq!(h.undo(2), Some(1));
+    h.record(1);
+    assert!(!h.can_redo());
+}
+
+#[test]
+fn test_undo_limit() {
+    let mut h = History::new(Some(1));
+    h.record(1);
+    h.record(2);
+    assert_eq!(h.undo(3), Some(2));
+    assert!(!h.can_undo());
+    assert_eq!(h.undo(2), None);
+
+    let mut disabled = History::new(Some(0));
+    disabled.record(1);
+    assert_eq!(disabled.undo(2), None);
+}
@@ -0,0 +1,38 @@
+//! The Auto Sage engine: Sage Solitaire's rules and scoring, plus the
+//! computer players, hints, odds and solver built on them. It has no
+//! frontend, so tools can use it without BearLibTerminal.
+//!
+//! A `Game` is dealt from a seed (or from a deal written in `notation`
+//! form) and changed by sending it `Msg`s with `update`, the same way the
+//! frontends do. A message that can't be applied leaves the game alone and
+//! returns a `GameError` saying why:
+//!
+//! ```
+//! use autosage_engine::{update, Game, GameError, Move, Msg, Position};
+//! use autosage_engine::model::{ColumnId, RowId};
+//!
+//! let mut game = Game::generate_with_seed(42);
+//! let top_left = Position(RowId::Top, ColumnId::Left);
+//! update(Msg::ToggleStack(top_left), &mut game).unwrap();
+//! assert_eq!(game.selected_move(), Some(Move::Trash(top_left)));
+//! update(Msg::MakeMove, &mut game).unwrap();
+//! assert_eq!(game.spread.get_stack(top_left).len(), 7);
+//! assert_eq!(update(Msg::Redo, &mut game), Err(GameError::NothingToRedo));
+//! ```
+//!
+//! `strategy` and `sim` play whole games; `solver` finds the best score for
+//! a deal whose cards are all known.
+pub mod display;
+pub mod hint;
+pub mod history;
+pub mod model;
+pub mod notation;
+pub mod odds;
+pub mod rules;
+pub mod sim;
+pub mod solver;
+pub mod strategy;
+pub mod update;
+
+pub use crate::model::{Card, Game, GameError, Hand, Move, Msg, Position, Spread};
+pub use crate::update::update;
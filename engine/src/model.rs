@@ -0,0 +1,1902 @@
+/// Raw representation of cards, stacks, the board, trashes, and the player's selection.
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::History;
+use crate::notation::{self, ParseError};
+use crate::rules::Ruleset;
+
+/// The nine stacks, top row first and left to right, with how many cards
+/// the standard rules deal to each.
+pub const PILE_SIZES: [(Position, usize); 9] = [
+    (Position(RowId::Top, ColumnId::Left), 8),
+    (Position(RowId::Top, ColumnId::Center), 8),
+    (Position(RowId::Top, ColumnId::Right), 8),
+    (Position(RowId::Middle, ColumnId::Left), 7),
+    (Position(RowId::Middle, ColumnId::Center), 6),
+    (Position(RowId::Middle, ColumnId::Right), 5),
+    (Position(RowId::Bottom, ColumnId::Left), 4),
+    (Position(RowId::Bottom, ColumnId::Center), 3),
+    (Position(RowId::Bottom, ColumnId::Right), 2),
+];
+
+#[test]
+fn test_pile_sizes() {
+    let mut total = 0;
+    for (_, size) in &PILE_SIZES {
+        total += size;
+    }
+    assert!(total == 51);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+pub enum Suit {
+    Club,
+    Diamond,
+    Heart,
+    Spade,
+}
+
+#[test]
+fn test_suit_ranking() {
+    // This also checks that Enum ordering works the way I think it does.
+    assert!(Suit::Spade == Suit::Spade);
+    assert!(Suit::Club < Suit::Diamond);
+    assert!(Suit::Diamond < Suit::Heart);
+    assert!(Suit::Heart < Suit::Spade);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rank {
+    Ace,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    fn successor(self) -> Rank {
+        match self {
+            Rank::Ace => Rank::Two,
+            Rank::Two => Rank::Three,
+            Rank::Three => Rank::Four,
+            Rank::Four => Rank::Five,
+            Rank::Five => Rank::Six,
+            Rank::Six => Rank::Seven,
+            Rank::Seven => Rank::Eight,
+            Rank::Eight => Rank::Nine,
+            Rank::Nine => Rank::Ten,
+            Rank::Ten => Rank::Jack,
+            Rank::Jack => Rank::Queen,
+            Rank::Queen => Rank::King,
+            Rank::King => Rank::Ace,
+        }
+    }
+}
+
+/// A playing card: its rank, then its suit.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash, Serialize, Deserialize)]
+pub struct Card(pub Rank, pub Suit);
+
+impl Card {
+    pub fn new(r: Rank, s: Suit) -> Self {
+        Card(r, s)
+    }
+}
+
+/// Representation of the 52-card deck.
+pub const SUITS: [Suit; 4] = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade];
+pub const RANKS: [Rank; 13] = [
+    Rank::Ace,
+    Rank::Two,
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+];
+
+pub type CardStack = Vec<Card>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum RowId {
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum ColumnId {
+    Left,
+    Center,
+    Right,
+}
+
+/// Where a stack is: its row, then its column.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub struct Position(pub RowId, pub ColumnId);
+
+/// A board with cards on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spread {
+    pub tl: CardStack,
+    pub tc: CardStack,
+    pub tr: CardStack,
+    pub ml: CardStack,
+    pub mc: CardStack,
+    pub mr: CardStack,
+    pub bl: CardStack,
+    pub bc: CardStack,
+    pub br: CardStack,
+}
+
+impl Spread {
+    pub fn empty() -> Self {
+        Spread {
+            tl: Vec::new(),
+            tc: Vec::new(),
+            tr: Vec::new(),
+            ml: Vec::new(),
+            mc: Vec::new(),
+            mr: Vec::new(),
+            bl: Vec::new(),
+            bc: Vec::new(),
+            br: Vec::new(),
+        }
+    }
+
+    /// The stack at `pos`, bottom card first.
+    pub fn get_stack(&self, pos: Position) -> &CardStack {
+        match pos {
+            Position(RowId::Top, ColumnId::Left) => &self.tl,
+            Position(RowId::Top, ColumnId::Center) => &self.tc,
+            Position(RowId::Top, ColumnId::Right) => &self.tr,
+            Position(RowId::Middle, ColumnId::Left) => &self.ml,
+            Position(RowId::Middle, ColumnId::Center) => &self.mc,
+            Position(RowId::Middle, ColumnId::Right) => &self.mr,
+            Position(RowId::Bottom, ColumnId::Left) => &self.bl,
+            Position(RowId::Bottom, ColumnId::Center) => &self.bc,
+            Position(RowId::Bottom, ColumnId::Right) => &self.br,
+        }
+    }
+    pub fn get_stack_mut(&mut self, pos: Position) -> &mut CardStack {
+        match pos {
+            Position(RowId::Top, ColumnId::Left) => &mut self.tl,
+            Position(RowId::Top, ColumnId::Center) => &mut self.tc,
+            Position(RowId::Top, ColumnId::Right) => &mut self.tr,
+            Position(RowId::Middle, ColumnId::Left) => &mut self.ml,
+            Position(RowId::Middle, ColumnId::Center) => &mut self.mc,
+            Position(RowId::Middle, ColumnId::Right) => &mut self.mr,
+            Position(RowId::Bottom, ColumnId::Left) => &mut self.bl,
+            Position(RowId::Bottom, ColumnId::Center) => &mut self.bc,
+            Position(RowId::Bottom, ColumnId::Right) => &mut self.br,
+        }
+    }
+}
+
+/// How many trashes the player has left.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum Trashes {
+    None,
+    One,
+    Two,
+}
+
+/// A game in progress: the spread, what the player has selected, and
+/// everything needed to score, undo and replay it.
+///
+/// Change a game by sending it a `Msg` with `update::update`; the methods
+/// here that change it directly skip the log that goes with that.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Game {
+    pub spread: Spread,
+    pub selected: HashSet<Position>,
+    pub trashes: Trashes,
+    pub bonus_card: Card,
+    /// A suggested move, shown until the player does something else.
+    #[serde(skip)]
+    pub hint: Option<(Move, HashSet<Position>)>,
+    hand_score: u32,
+    history: History<Snapshot>,
+    seed: u64,
+    /// The deal in `notation` form, if it was given instead of dealt from
+    /// the seed.
+    #[serde(default)]
+    custom_deal: Option<String>,
+    /// Everything that has happened since the deal, including undos.
+    #[serde(default)]
+    log: Vec<LogEntry>,
+    #[serde(default)]
+    rules: Ruleset,
+    /// Every card taken off the spread, in the order it went.
+    #[serde(default)]
+    discards: Vec<Discard>,
+    /// This is the attempt at a daily deal that counts. Starting over in
+    /// any way clears it.
+    #[serde(default)]
+    daily: bool,
+}
+
+/// Everything about a game that a move can change, kept for undo and redo.
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    spread: Spread,
+    selected: HashSet<Position>,
+    trashes: Trashes,
+    hand_score: u32,
+    #[serde(default)]
+    discards: Vec<Discard>,
+}
+
+/// A hand that can be played, from least to most valuable.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum Hand {
+    Pair,
+    StraightThree,
+    ThreeOfAKind,
+    StraightFive,
+    FullHouse,
+    Flush,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// Something the player can do, handled by `update::update`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Msg {
+    MakeMove,
+    ToggleStack(Position),
+    NewGame,
+    Undo,
+    Redo,
+    RestartDeal,
+    /// Suggest a move, or select the suggestion if one is showing.
+    Hint,
+}
+
+/// A move that takes cards off the spread.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Move {
+    Trash(Position),
+    PlayHand(Hand),
+}
+
+/// What a message did to the game, for the move log.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum Effect {
+    /// A move was made with these stacks selected (in `PILE_SIZES` order).
+    Played(Move, Vec<Position>),
+    Undone,
+    Redone,
+}
+
+/// One message applied to the game and what came of it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub msg: Msg,
+    /// `None` if the message only changed the selection, or did nothing.
+    pub effect: Option<Effect>,
+    pub score_delta: i32,
+}
+
+/// Why a change to a game couldn't be made. The game is left as it was.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameError {
+    /// The game is won or lost.
+    GameOver,
+    /// A stack with no cards was selected or played from.
+    EmptyStack(Position),
+    NothingSelected,
+    NoTrashes,
+    /// The selected cards don't make any hand.
+    NotAHand(Unplayable),
+    /// The selected cards don't make this hand.
+    NotThatHand(Hand),
+    NothingToUndo,
+    /// Every undo the game allows has been used.
+    UndoLimit(u32),
+    NothingToRedo,
+    /// The rules deal more cards than the deck has.
+    Overdraw {
+        wanted: usize,
+        left: usize,
+    },
+    /// The rules leave this many cards besides the bonus card undealt.
+    Underdeal(usize),
+    /// The custom deal can't be read, or doesn't fit the rules.
+    BadDeal(ParseError),
+}
+
+/// Why the selected stacks can't be played.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Unplayable {
+    /// A selected stack has no cards.
+    EmptyStack(Position),
+    /// A single card can only be trashed, and there are no trashes left.
+    NoTrashes,
+    /// No hand has this many cards.
+    WrongCount(usize),
+    /// Two or four cards have to share a rank.
+    RanksDiffer,
+    /// Three cards have to share a rank or run in order.
+    NotConsecutive,
+    /// The ranks only run in order by going from King round to Two.
+    WrapsAround,
+    /// Five cards that are no straight, flush or full house.
+    NoFiveCardHand,
+    /// The cards make a hand, but aren't spread over enough rows.
+    TooFewRows { spanned: usize, needed: usize },
+}
+
+/// What a move would score, piece by piece.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Preview {
+    pub mv: Move,
+    /// The hand's points before the bonus suit; 0 for a trash.
+    pub base: u32,
+    /// What the bonus suit multiplies the hand by, or 1 if it doesn't.
+    pub multiplier: u32,
+    /// The piles the move would clear, with the bonus for each.
+    pub cleared: Vec<(Position, u32)>,
+}
+
+impl Preview {
+    /// How much the score would go up.
+    pub fn total(&self) -> u32 {
+        self.base * self.multiplier + self.cleared.iter().map(|(_, b)| b).sum::<u32>()
+    }
+}
+
+/// A card taken off the spread, and how it went.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Discard {
+    pub card: Card,
+    /// The trash or hand that took it.
+    pub cause: Move,
+    /// Which move of the game it went in, counting from 1. Moves that were
+    /// undone don't count.
+    pub turn: u32,
+}
+
+/// Cards that the spread, the discards and the bonus card don't hold
+/// exactly once between them.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Unaccounted {
+    pub missing: Vec<Card>,
+    pub duplicated: Vec<Card>,
+}
+
+/// Whether a game can still be played, and how it ended if not.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    InProgress,
+    /// Every stack has been cleared.
+    Won,
+    /// Cards remain but there's no hand to play and no trash to spend.
+    Lost,
+}
+
+// -------------------------------------------------
+// Predicates
+
+// NB: Tests for predicates are in the test module below
+
+fn all_eq<I, X>(xs: I) -> bool
+where
+    X: std::cmp::Eq,
+    I: IntoIterator<Item = X>,
+{
+    let mut iter = xs.into_iter();
+    let fst = match iter.next() {
+        Some(x) => x,
+        None => return true,
+    };
+    iter.all(|x| x == fst)
+}
+
+fn is_straight(cards: &HashSet<Card>) -> bool {
+    let mut sorted_cards = {
+        let mut cs: Vec<_> = cards.iter().collect();
+        cs.sort();
+        cs
+    };
+    // Check specifically if the ace should be high
+    if sorted_cards.len() > 2 {
+        let Card(br, _) = sorted_cards[0];
+        let Card(tr, _) = sorted_cards.last().unwrap();
+        if *br == Rank::Ace && *tr == Rank::King {
+            let ace = sorted_cards.remove(0);
+            let Card(r, _) = ace;
+            assert!(*r == Rank::Ace);
+            sorted_cards.push(ace);
+        }
+    }
+    // Otherwise, check normally
+    for idx in 0..(sorted_cards.len() - 1) {
+        let Card(r1, _) = sorted_cards[idx];
+        let Card(r2, _) = sorted_cards[idx + 1];
+        if *r2 != r1.successor() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether the ranks would be a straight if they could go from King round
+/// to Two, like K-A-2.
+fn wraps_around(cards: &HashSet<Card>) -> bool {
+    let ranks: HashSet<usize> = cards
+        .iter()
+        .filter_map(|Card(r, _)| RANKS.iter().position(|x| x == r))
+        .collect();
+    if ranks.len() < 3 || ranks.len() != cards.len() || is_straight(cards) {
+        return false;
+    }
+    (0..RANKS.len()).any(|start| {
+        (0..ranks.len()).all(|offset| ranks.contains(&((start + offset) % RANKS.len())))
+    })
+}
+
+// -----------------------------------
+// Hand detection
+
+/// Which hand a set of top cards makes, if any, wherever they were taken
+/// from.
+pub fn classify_hand(cards: &HashSet<Card>) -> Option<Hand> {
+    match cards.len() {
+        2 => {
+            if all_eq(cards.iter().map(|Card(r, _)| r)) {
+                Some(Hand::Pair)
+            } else {
+                None
+            }
+        }
+        3 => {
+            //straight, 3oC
+            if all_eq(cards.iter().map(|Card(r, _)| r)) {
+                Some(Hand::ThreeOfAKind)
+            } else if is_straight(cards) {
+                Some(Hand::StraightThree)
+            } else {
+                None
+            }
+        }
+        4 => {
+            if all_eq(cards.iter().map(|Card(r, _)| r)) {
+                Some(Hand::FourOfAKind)
+            } else {
+                None
+            }
+        }
+        5 => {
+            if check_fullhouse(cards) {
+                return Some(Hand::FullHouse);
+            }
+            let straight = is_straight(cards);
+            let flush = all_eq(cards.iter().map(|Card(_, s)| s));
+            match (straight, flush) {
+                (true, true) => Some(Hand::StraightFlush),
+                (true, false) => Some(Hand::StraightFive),
+                (false, true) => Some(Hand::Flush),
+                (false, false) => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn rows_spanned(positions: &HashSet<Position>) -> usize {
+    let mut rs: HashSet<RowId> = HashSet::new();
+    for Position(r, _) in positions.iter() {
+        rs.insert(*r);
+    }
+    rs.len()
+}
+
+fn check_fullhouse(cards: &HashSet<Card>) -> bool {
+    use std::collections::HashMap;
+    let mut ranks: HashMap<Rank, usize> = HashMap::new();
+    for Card(r, _) in cards.iter() {
+        let cnt = ranks.entry(*r).or_insert(0);
+        *cnt += 1;
+    }
+    let mut vals: Vec<usize> = ranks.values().copied().collect();
+    vals.sort();
+    vals == vec![2, 3]
+}
+
+impl Game {
+    // ----------------------------------------------------
+    // Generation
+    /// Deal a game from a random seed, under the standard rules.
+    pub fn generate() -> Game {
+        Game::generate_with_seed(random_seed())
+    }
+
+    /// Deal a game from a seed, under the standard rules. The same seed
+    /// always produces the same deal.
+    pub fn generate_with_seed(seed: u64) -> Game {
+        let rules = Ruleset::standard();
+        let (spread, bonus_card) =
+            deal(seed, &rules.pile_sizes).expect("The standard rules deal the whole deck");
+        Game {
+            spread,
+            selected: HashSet::new(),
+            trashes: rules.max_trashes,
+            bonus_card,
+            hint: None,
+            hand_score: 0,
+            history: History::new(None),
+            seed,
+            custom_deal: None,
+            log: Vec::new(),
+            rules,
+            discards: Vec::new(),
+            daily: false,
+        }
+    }
+
+    /// The same deal, started over under different rules. Fails if the
+    /// rules' pile sizes don't deal the deck.
+    pub fn with_rules(mut self, rules: Ruleset) -> Result<Game, GameError> {
+        self.rules = rules;
+        self.restart()?;
+        Ok(self)
+    }
+
+    /// Start from a deal written in `notation` form instead of a seed.
+    pub fn from_notation(src: &str) -> Result<Game, ParseError> {
+        let (spread, bonus_card) = notation::parse(src)?;
+        let mut g = Game::generate_with_seed(0);
+        notation::check_sizes(&spread, &g.rules.pile_sizes)?;
+        g.spread = spread;
+        g.bonus_card = bonus_card;
+        g.custom_deal = Some(notation::write(&g.spread, g.bonus_card));
+        Ok(g)
+    }
+
+    /// The deal this game started from, in `notation` form.
+    pub fn to_notation(&self) -> String {
+        match &self.custom_deal {
+            Some(src) => src.clone(),
+            None => {
+                let (spread, bonus_card) = deal(self.seed, &self.rules.pile_sizes)
+                    .expect("The game was dealt under these rules");
+                notation::write(&spread, bonus_card)
+            }
+        }
+    }
+
+    /// Check that the deal can be made again under the game's rules, as
+    /// restarting it needs. A game read from a file might not be.
+    pub fn check_deal(&self) -> Result<(), GameError> {
+        match &self.custom_deal {
+            Some(src) => {
+                let (spread, _) = notation::parse(src).map_err(GameError::BadDeal)?;
+                notation::check_sizes(&spread, &self.rules.pile_sizes).map_err(GameError::BadDeal)
+            }
+            None => deal(self.seed, &self.rules.pile_sizes).map(|_| ()),
+        }
+    }
+
+    /// Whether the deal came from `from_notation` rather than a seed.
+    pub fn is_custom_deal(&self) -> bool {
+        self.custom_deal.is_some()
+    }
+
+    /// Mark this game as the counted attempt at a daily deal.
+    pub fn mark_daily(&mut self) {
+        self.daily = true;
+    }
+
+    pub fn is_daily(&self) -> bool {
+        self.daily
+    }
+
+    /// Deal a new game from a random seed, keeping the rules.
+    pub fn reset(&mut self) -> Result<(), GameError> {
+        self.reset_with_seed(random_seed())
+    }
+
+    /// Deal a new game from `seed`, keeping the rules.
+    pub fn reset_with_seed(&mut self, seed: u64) -> Result<(), GameError> {
+        let (spread, bonus_card) = deal(seed, &self.rules.pile_sizes)?;
+        self.start_over(spread, bonus_card);
+        self.seed = seed;
+        self.custom_deal = None;
+        Ok(())
+    }
+
+    fn start_over(&mut self, spread: Spread, bonus_card: Card) {
+        self.selected.clear();
+        self.trashes = self.rules.max_trashes;
+        self.hand_score = 0;
+        self.history.clear();
+        self.log.clear();
+        self.discards.clear();
+        self.daily = false;
+        self.spread = spread;
+        self.bonus_card = bonus_card;
+    }
+
+    /// Start the current deal over from the beginning.
+    pub fn restart(&mut self) -> Result<(), GameError> {
+        let src = match &self.custom_deal {
+            Some(src) => src,
+            None => return self.reset_with_seed(self.seed),
+        };
+        let (spread, bonus_card) = notation::parse(src).map_err(GameError::BadDeal)?;
+        notation::check_sizes(&spread, &self.rules.pile_sizes).map_err(GameError::BadDeal)?;
+        self.start_over(spread, bonus_card);
+        Ok(())
+    }
+
+    /// The seed this game was dealt from. Meaningless for a custom deal.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The rules this game is played under.
+    pub fn rules(&self) -> &Ruleset {
+        &self.rules
+    }
+
+    /// Every message applied since the deal, oldest first.
+    pub fn log(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    /// Add to the log. `update::update` does this for every message.
+    pub fn record(&mut self, entry: LogEntry) {
+        self.log.push(entry);
+    }
+
+    /// The moves that led to the current position, leaving out any that
+    /// were undone.
+    pub fn moves_made(&self) -> Vec<Move> {
+        let mut made = Vec::new();
+        let mut undone = Vec::new();
+        for entry in self.log.iter() {
+            match &entry.effect {
+                Some(Effect::Played(mv, _)) => {
+                    made.push(*mv);
+                    undone.clear();
+                }
+                Some(Effect::Undone) => undone.extend(made.pop()),
+                Some(Effect::Redone) => made.extend(undone.pop()),
+                None => (),
+            }
+        }
+        made
+    }
+
+    // ----------------------------------------------------
+    // History
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            spread: self.spread.clone(),
+            selected: self.selected.clone(),
+            trashes: self.trashes,
+            hand_score: self.hand_score,
+            discards: self.discards.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.spread = snapshot.spread;
+        self.selected = snapshot.selected;
+        self.trashes = snapshot.trashes;
+        self.hand_score = snapshot.hand_score;
+        self.discards = snapshot.discards;
+    }
+
+    /// Remember the current state so the next move can be undone.
+    pub fn checkpoint(&mut self) {
+        let snapshot = self.snapshot();
+        self.history.record(snapshot);
+    }
+
+    /// Go back to the last checkpoint.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        if !self.history.can_undo() {
+            return Err(match self.undo_limit() {
+                Some(limit) if self.undos_used() >= limit => GameError::UndoLimit(limit),
+                _ => GameError::NothingToUndo,
+            });
+        }
+        let current = self.snapshot();
+        let prev = self.history.undo(current).ok_or(GameError::NothingToUndo)?;
+        self.restore(prev);
+        Ok(())
+    }
+
+    /// Re-apply the last undone move.
+    pub fn redo(&mut self) -> Result<(), GameError> {
+        if !self.history.can_redo() {
+            return Err(GameError::NothingToRedo);
+        }
+        let current = self.snapshot();
+        let next = self.history.redo(current).ok_or(GameError::NothingToRedo)?;
+        self.restore(next);
+        Ok(())
+    }
+
+    /// How many times a move has been undone this game.
+    pub fn undos_used(&self) -> u32 {
+        self.history.undos_used()
+    }
+
+    /// How many undos are allowed, if there's a limit.
+    pub fn undo_limit(&self) -> Option<u32> {
+        self.history.limit()
+    }
+
+    pub fn set_undo_limit(&mut self, limit: Option<u32>) {
+        self.history.set_limit(limit);
+    }
+
+    // ----------------------------------------------------
+    // Selection and scoring
+
+    fn selected_cards(&self) -> Result<HashSet<Card>, Position> {
+        self.cards_at(&self.selected)
+    }
+
+    /// The top cards of `positions`, or the first of them that's empty.
+    fn cards_at(&self, positions: &HashSet<Position>) -> Result<HashSet<Card>, Position> {
+        let mut cards = HashSet::new();
+        for p in positions.iter() {
+            let card = self.spread.get_stack(*p).last().ok_or(*p)?;
+            cards.insert(*card);
+        }
+        Ok(cards)
+    }
+
+    /// The hand the selected stacks' top cards make, if any.
+    pub fn selected_hand(&self) -> Option<Hand> {
+        self.hand_at(&self.selected)
+    }
+
+    /// The hand the top cards of `positions` would make, if any. There's
+    /// no hand if one of the positions is empty.
+    pub fn hand_at(&self, positions: &HashSet<Position>) -> Option<Hand> {
+        if rows_spanned(positions) < self.rules.min_rows {
+            return None;
+        }
+        classify_hand(&self.cards_at(positions).ok()?)
+    }
+
+    /// Use up a trash.
+    pub fn spend_one_trash(&mut self) -> Result<(), GameError> {
+        self.trashes = match self.trashes {
+            Trashes::Two => Trashes::One,
+            Trashes::One => Trashes::None,
+            Trashes::None => return Err(GameError::NoTrashes),
+        };
+        Ok(())
+    }
+
+    /// Earn back a trash, unless the rules don't allow any more.
+    pub fn restore_one_trash(&mut self) {
+        let restored = match self.trashes {
+            Trashes::Two => Trashes::Two,
+            Trashes::One => Trashes::Two,
+            Trashes::None => Trashes::One,
+        };
+        self.trashes = restored.min(self.rules.max_trashes);
+    }
+
+    #[cfg(test)]
+    fn selected_rows(&self) -> usize {
+        rows_spanned(&self.selected)
+    }
+
+    /// What would happen if the selection were played: a trash for one
+    /// stack, a hand for several, or nothing if that isn't allowed.
+    pub fn selected_move(&self) -> Option<Move> {
+        self.check_move().ok()
+    }
+
+    /// The move the selection would make, or why it can't be made.
+    pub fn check_move(&self) -> Result<Move, GameError> {
+        match self.judge_selection() {
+            Ok(Some(mv)) => Ok(mv),
+            Ok(None) => Err(GameError::NothingSelected),
+            Err(Unplayable::EmptyStack(p)) => Err(GameError::EmptyStack(p)),
+            Err(Unplayable::NoTrashes) => Err(GameError::NoTrashes),
+            Err(reason) => Err(GameError::NotAHand(reason)),
+        }
+    }
+
+    /// What the selected move would score, if there is one.
+    pub fn preview_move(&self) -> Option<Preview> {
+        let mv = self.selected_move()?;
+        Some(self.preview(mv, &self.selected))
+    }
+
+    /// What `mv` would score when made with `positions` selected.
+    pub fn preview(&self, mv: Move, positions: &HashSet<Position>) -> Preview {
+        let (base, multiplier) = match mv {
+            Move::Trash(_) => (0, 1),
+            Move::PlayHand(h) => {
+                let cards = self.cards_at(positions).unwrap_or_default();
+                (self.rules.points(h), self.multiplier(&cards))
+            }
+        };
+        let cleared = PILE_SIZES
+            .iter()
+            .map(|(pos, _)| *pos)
+            .filter(|pos| positions.contains(pos) && self.spread.get_stack(*pos).len() == 1)
+            .map(|pos @ Position(rowid, _)| (pos, self.rules.row_bonus(rowid)))
+            .collect();
+        Preview {
+            mv,
+            base,
+            multiplier,
+            cleared,
+        }
+    }
+
+    /// Why the selection can't be played. `None` if it can, or if nothing
+    /// is selected.
+    pub fn explain_selection(&self) -> Option<Unplayable> {
+        self.judge_selection().err()
+    }
+
+    fn judge_selection(&self) -> Result<Option<Move>, Unplayable> {
+        let cards = self.selected_cards().map_err(Unplayable::EmptyStack)?;
+        match cards.len() {
+            0 => Ok(None),
+            1 if self.trashes == Trashes::None => Err(Unplayable::NoTrashes),
+            1 => Ok(self.selected.iter().next().map(|pos| Move::Trash(*pos))),
+            n if n > 5 => Err(Unplayable::WrongCount(n)),
+            n => match classify_hand(&cards) {
+                Some(h) => {
+                    let spanned = rows_spanned(&self.selected);
+                    let needed = self.rules.min_rows;
+                    if spanned < needed {
+                        Err(Unplayable::TooFewRows { spanned, needed })
+                    } else {
+                        Ok(Some(Move::PlayHand(h)))
+                    }
+                }
+                // There's no straight of four, wrapped or not
+                None if n == 4 && (is_straight(&cards) || wraps_around(&cards)) => {
+                    Err(Unplayable::WrongCount(n))
+                }
+                None if wraps_around(&cards) => Err(Unplayable::WrapsAround),
+                None if n == 3 => Err(Unplayable::NotConsecutive),
+                None if n == 5 => Err(Unplayable::NoFiveCardHand),
+                None => Err(Unplayable::RanksDiffer),
+            },
+        }
+    }
+
+    /// Every card still in the spread, seen or not.
+    pub fn remaining_cards(&self) -> HashSet<Card> {
+        let mut cards = HashSet::new();
+        let mut add_stack = |st: &Vec<Card>| {
+            for c in st {
+                cards.insert(*c);
+            }
+        };
+        add_stack(&self.spread.tl);
+        add_stack(&self.spread.tc);
+        add_stack(&self.spread.tr);
+        add_stack(&self.spread.ml);
+        add_stack(&self.spread.mc);
+        add_stack(&self.spread.mr);
+        add_stack(&self.spread.bl);
+        add_stack(&self.spread.bc);
+        add_stack(&self.spread.br);
+        cards
+    }
+
+    /// Score the selected hand and take its cards off the spread. The
+    /// selection must make `h`.
+    pub fn play_hand(&mut self, h: Hand) -> Result<(), GameError> {
+        let cards = self.selected_cards().map_err(GameError::EmptyStack)?;
+        if self.selected_hand() != Some(h) {
+            return Err(GameError::NotThatHand(h));
+        }
+        self.score_hand(h, &cards);
+        let turn = self.next_turn();
+        for (pos, _) in PILE_SIZES.iter() {
+            if self.selected.contains(pos) {
+                self.discard(*pos, Move::PlayHand(h), turn);
+            }
+        }
+        self.restore_one_trash();
+        self.selected.clear();
+        Ok(())
+    }
+
+    /// Spend a trash to take the top card off `pos`, clearing the selection.
+    pub fn trash(&mut self, pos: Position) -> Result<(), GameError> {
+        if self.spread.get_stack(pos).is_empty() {
+            return Err(GameError::EmptyStack(pos));
+        }
+        self.spend_one_trash()?;
+        let turn = self.next_turn();
+        self.discard(pos, Move::Trash(pos), turn);
+        self.selected.clear();
+        Ok(())
+    }
+
+    fn discard(&mut self, pos: Position, cause: Move, turn: u32) {
+        if let Some(card) = self.spread.get_stack_mut(pos).pop() {
+            self.discards.push(Discard { card, cause, turn });
+        }
+    }
+
+    fn next_turn(&self) -> u32 {
+        self.discards.last().map_or(1, |d| d.turn + 1)
+    }
+
+    /// Every card taken off the spread so far, oldest first. Games saved
+    /// before discards were kept start with none.
+    pub fn discards(&self) -> &[Discard] {
+        &self.discards
+    }
+
+    /// Check that every card in the deck is in the spread, the discards or
+    /// the bonus card, and only once.
+    pub fn check_conservation(&self) -> Result<(), Unaccounted> {
+        let mut counts: HashMap<Card, usize> = HashMap::new();
+        let spread = PILE_SIZES
+            .iter()
+            .flat_map(|(pos, _)| self.spread.get_stack(*pos).iter());
+        let discards = self.discards.iter().map(|d| &d.card);
+        for card in spread
+            .chain(discards)
+            .chain(std::iter::once(&self.bonus_card))
+        {
+            *counts.entry(*card).or_insert(0) += 1;
+        }
+        let mut unaccounted = Unaccounted::default();
+        for &r in RANKS.iter() {
+            for &s in SUITS.iter() {
+                match counts.get(&Card(r, s)) {
+                    None => unaccounted.missing.push(Card(r, s)),
+                    Some(1) => (),
+                    Some(_) => unaccounted.duplicated.push(Card(r, s)),
+                }
+            }
+        }
+        if unaccounted == Unaccounted::default() {
+            Ok(())
+        } else {
+            Err(unaccounted)
+        }
+    }
+
+    fn score_hand(&mut self, h: Hand, cards: &HashSet<Card>) {
+        self.hand_score += self.multiplier(cards) * self.rules.points(h);
+    }
+
+    /// What a hand of `cards` is multiplied by: more than 1 if any is of
+    /// the bonus suit.
+    fn multiplier(&self, cards: &HashSet<Card>) -> u32 {
+        let Card(_, bonus_suit) = self.bonus_card;
+        if cards.iter().any(|Card(_, s)| *s == bonus_suit) {
+            self.rules.bonus_multiplier
+        } else {
+            1
+        }
+    }
+
+    /// Points for hands played and piles cleared.
+    pub fn score(&self) -> u32 {
+        self.hand_score + self.pile_bonus()
+    }
+
+    /// Points earned from played hands, not counting cleared piles.
+    pub fn hand_score(&self) -> u32 {
+        self.hand_score
+    }
+
+    /// Points earned from cleared piles.
+    pub fn pile_bonus(&self) -> u32 {
+        let mut pile_bonus: u32 = 0;
+        for rowid in &[RowId::Top, RowId::Middle, RowId::Bottom] {
+            let bonus = self.rules.row_bonus(*rowid);
+            for colid in &[ColumnId::Left, ColumnId::Center, ColumnId::Right] {
+                if self.spread.get_stack(Position(*rowid, *colid)).is_empty() {
+                    pile_bonus += bonus;
+                }
+            }
+        }
+        pile_bonus
+    }
+
+    /// How many cards are still in the spread.
+    pub fn cards_left(&self) -> usize {
+        PILE_SIZES
+            .iter()
+            .map(|(pos, _)| self.spread.get_stack(*pos).len())
+            .sum()
+    }
+
+    /// Whether the game can go on, and how it ended if not.
+    pub fn status(&self) -> Status {
+        if self.cards_left() == 0 {
+            Status::Won
+        } else if !self.legal_moves().is_empty() {
+            Status::InProgress
+        } else {
+            Status::Lost
+        }
+    }
+
+    /// Every move that could be made on the current spread, with the stacks
+    /// that would have to be selected to make it.
+    ///
+    /// Trashes come first (in `PILE_SIZES` order), followed by hands. The
+    /// player's selection is ignored.
+    pub fn legal_moves(&self) -> Vec<(Move, HashSet<Position>)> {
+        let mut moves = Vec::new();
+        if self.trashes != Trashes::None {
+            for pos in self.occupied_positions() {
+                let mut positions = HashSet::new();
+                positions.insert(pos);
+                moves.push((Move::Trash(pos), positions));
+            }
+        }
+        for (h, positions) in self.playable_hands() {
+            moves.push((Move::PlayHand(h), positions));
+        }
+        moves
+    }
+
+    fn occupied_positions(&self) -> Vec<Position> {
+        PILE_SIZES
+            .iter()
+            .map(|(pos, _)| *pos)
+            .filter(|pos| !self.spread.get_stack(*pos).is_empty())
+            .collect()
+    }
+
+    /// Check every combination of top cards for a playable hand.
+    fn playable_hands(&self) -> Vec<(Hand, HashSet<Position>)> {
+        let occupied = self.occupied_positions();
+        let mut hands = Vec::new();
+        for mask in 0u32..(1 << occupied.len()) {
+            let size = mask.count_ones();
+            if !(2..=5).contains(&size) {
+                continue;
+            }
+            let positions: HashSet<Position> = occupied
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| mask & (1 << idx) != 0)
+                .map(|(_, pos)| *pos)
+                .collect();
+            if let Some(h) = self.hand_at(&positions) {
+                hands.push((h, positions));
+            }
+        }
+        hands
+    }
+}
+
+#[cfg(test)]
+mod test_game_logic {
+    use super::*;
+
+    // -----------------------------------------------------------
+    // Testing helpers
+
+    fn c(src: &str) -> Card {
+        notation::parse_card(src).expect("Invalid card")
+    }
+
+    macro_rules! cards {
+        ( $( $csrc:expr ),* ) => {
+            {
+                let mut cards = HashSet::new();
+                $(
+                    cards.insert(c($csrc));
+                )*
+                cards
+            }
+        }
+    }
+
+    fn p(src: &str) -> Position {
+        let mut chars = src.chars();
+        let r = match chars.next().expect("need a row") {
+            't' => RowId::Top,
+            'm' => RowId::Middle,
+            'b' => RowId::Bottom,
+            _ => panic!("Invalid row char"),
+        };
+        let c = match chars.next().expect("need a column") {
+            'l' => ColumnId::Left,
+            'c' => ColumnId::Center,
+            'r' => ColumnId::Right,
+            _ => panic!("Invalid column char"),
+        };
+        Position(r, c)
+    }
+
+    fn insert_card(g: &mut Game, p_src: &str, c_src: &str) {
+        let s = g.spread.get_stack_mut(p(p_src));
+        s.push(c(c_src));
+    }
+
+    impl Game {
+        fn empty() -> Game {
+            Game {
+                spread: Spread::empty(),
+                selected: HashSet::new(),
+                bonus_card: c("as"),
+                trashes: Trashes::Two,
+                hint: None,
+                hand_score: 0,
+                history: History::new(None),
+                seed: 0,
+                custom_deal: None,
+                log: Vec::new(),
+                rules: Ruleset::standard(),
+                discards: Vec::new(),
+                daily: false,
+            }
+        }
+
+        fn select(&mut self, p_srcs: &str) {
+            let p_atoms = p_srcs.split(' ');
+            self.selected.clear();
+            for p_src in p_atoms {
+                self.selected.insert(p(p_src));
+            }
+        }
+    }
+
+    // ----------------------------------------------------
+    // Predicate tests
+
+    #[test]
+    fn test_all_eq() {
+        let empty: Vec<u8> = vec![];
+        assert!(all_eq(&empty));
+        let one = vec![0];
+        assert!(all_eq(&one));
+        let two_match = vec![0, 0];
+        assert!(all_eq(&two_match));
+        let three_match = vec![1, 1, 1];
+        assert!(all_eq(&three_match));
+        let two_mismatch = vec![1, 0];
+        assert!(!all_eq(&two_mismatch));
+        let three_mismatch = vec![1, 1, 0];
+        assert!(!all_eq(&three_mismatch))
+    }
+
+    #[test]
+    fn test_is_straight() {
+        assert!(is_straight(&cards!("as", "2s", "3s")));
+        assert!(is_straight(&cards!("3s", "as", "2s")));
+        assert!(is_straight(&cards!("9d", "0c", "jh", "qs")));
+        assert!(is_straight(&cards!("jd", "qh", "ks", "ac")));
+    }
+    #[test]
+    fn test_is_not_straight() {
+        assert!(!is_straight(&cards!("as", "2s", "5s")));
+        assert!(!is_straight(&cards!("kd", "as", "2h")));
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        assert!(wraps_around(&cards!("kd", "as", "2h")));
+        assert!(wraps_around(&cards!("qd", "kc", "as", "2h", "3h")));
+        assert!(!wraps_around(&cards!("qd", "kc", "as")));
+        assert!(!wraps_around(&cards!("kd", "as", "3h")));
+        assert!(!wraps_around(&cards!("kd", "as")));
+    }
+
+    // ----------------------------------------------------
+    // Board querying
+
+    #[test]
+    fn test_rows_counting() {
+        let mut g = Game::empty();
+        assert_eq!(g.selected_rows(), 0);
+        g.selected.insert(p("tl"));
+        assert_eq!(g.selected_rows(), 1);
+        g.selected.insert(p("tc"));
+        assert_eq!(g.selected_rows(), 1);
+        g.selected.insert(p("mr"));
+        assert_eq!(g.selected_rows(), 2);
+        g.selected.insert(p("bl"));
+        assert_eq!(g.selected_rows(), 3);
+    }
+
+    // -------------------------------------------------------
+    // Hands
+    #[test]
+    fn check_no_selection_has_no_move() {
+        let g = Game::empty();
+        assert!(g.selected_hand().is_none())
+    }
+
+    #[test]
+    fn check_one_selected_card_has_no_hand() {
+        let mut g = Game::empty();
+        insert_card(&mut g, "tl", "2s");
+        g.selected.insert(p("tl"));
+        assert_eq!(g.selected.len(), 1);
+        assert!(g.selected_hand().is_none());
+    }
+
+    #[test]
+    fn check_pairs() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "2s");
+        insert_card(g, "tr", "2c");
+        insert_card(g, "bl", "2h");
+        insert_card(g, "br", "3s");
+        g.select("tl tr");
+        assert!(g.selected_hand().is_none(), "Pair in same row is no hand");
+        g.select("tl br");
+        assert!(g.selected_hand().is_none(), "Not-equal cards are not pair");
+        g.select("tl bl");
+        assert!(g.selected_hand().is_some());
+        assert_eq!(
+            g.selected_hand().expect("Expected to have selected a pair"),
+            Hand::Pair
+        );
+    }
+
+    #[test]
+    fn check_3_of_a_kind() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "ad");
+        insert_card(g, "tr", "ah");
+        insert_card(g, "ml", "ac");
+        insert_card(g, "mc", "2h");
+        g.select("tl tc tr");
+        assert!(
+            g.selected_hand().is_none(),
+            "One row can't be a three-of-a-kind"
+        );
+        g.select("tl tc ml");
+        assert_eq!(g.selected_hand().unwrap(), Hand::ThreeOfAKind);
+        g.select("tl tc mc");
+        assert!(
+            g.selected_hand().is_none(),
+            "Three cards with a mach isn't a three-off-a-kind"
+        );
+        g.select("tl tc ml mc");
+        assert!(
+            g.selected_hand().is_none(),
+            "Four with three matches isn't a three-of-a-kind"
+        );
+    }
+
+    #[test]
+    fn test_small_straight() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "2s");
+        insert_card(g, "tr", "3s");
+        insert_card(g, "ml", "3c");
+        insert_card(g, "mc", "ks");
+        g.select("tl tc tr");
+        assert!(g.selected_hand().is_none(), "One row cant be a straight");
+        g.select("tl tc ml");
+        assert_eq!(g.selected_hand().unwrap(), Hand::StraightThree);
+        g.select("tl tc mc");
+        assert!(g.selected_hand().is_none(), "Can't wrap a straight");
+    }
+
+    #[test]
+    fn test_four_of_a_kind() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "ad");
+        insert_card(g, "tr", "ah");
+        insert_card(g, "ml", "ac");
+        insert_card(g, "mc", "2c");
+        g.select("tl tc tr ml");
+        assert_eq!(g.selected_hand().unwrap(), Hand::FourOfAKind);
+        g.select("tl tc tr mc");
+        assert!(g.selected_hand().is_none());
+    }
+
+    #[test]
+    fn test_straight() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ah");
+        insert_card(g, "tc", "2h");
+        insert_card(g, "tr", "3h");
+        insert_card(g, "ml", "4d");
+        insert_card(g, "mc", "5d");
+        insert_card(g, "mr", "ks");
+        g.select("tl tc tr ml mc");
+        assert_eq!(g.selected_hand().unwrap(), Hand::StraightFive);
+        g.select("tl tc tr ml mr");
+        assert!(g.selected_hand().is_none());
+    }
+
+    #[test]
+    fn test_straight_flush() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ah");
+        insert_card(g, "tc", "2h");
+        insert_card(g, "tr", "3h");
+        insert_card(g, "ml", "4h");
+        insert_card(g, "mc", "5h");
+        insert_card(g, "mr", "ks");
+        insert_card(g, "bl", "5d");
+
+        g.select("tl tc tr ml mc");
+        assert_eq!(g.selected_hand().unwrap(), Hand::StraightFlush);
+
+        g.select("tl tc tr ml mr");
+        assert!(g.selected_hand().is_none());
+
+        g.select("tl tc tr ml bl");
+        assert_eq!(
+            g.selected_hand().unwrap(),
+            Hand::StraightFive,
+            "Mixed suit means straight, not straight flush"
+        );
+    }
+
+    #[test]
+    fn test_full_house() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "ac");
+        insert_card(g, "tr", "ad");
+        insert_card(g, "ml", "8s");
+        insert_card(g, "mc", "8d");
+        g.select("tl tc tr ml mc");
+        assert_eq!(g.selected_hand().unwrap(), Hand::FullHouse);
+    }
+
+    // -------------------------------------------------------
+    // Game status
+
+    #[test]
+    fn test_empty_spread_is_won() {
+        let g = Game::empty();
+        assert_eq!(g.status(), Status::Won);
+        assert_eq!(g.cards_left(), 0);
+        assert_eq!(g.pile_bonus(), 90);
+    }
+
+    #[test]
+    fn test_trashes_keep_game_going() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "2d");
+        assert_eq!(g.status(), Status::InProgress);
+        g.trashes = Trashes::None;
+        assert_eq!(g.status(), Status::Lost);
+    }
+
+    #[test]
+    fn test_available_hand_keeps_game_going() {
+        let g = &mut Game::empty();
+        g.trashes = Trashes::None;
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "ad");
+        assert_eq!(g.status(), Status::Lost, "Pair in one row isn't playable");
+        insert_card(g, "mr", "ah");
+        assert_eq!(g.status(), Status::InProgress);
+    }
+
+    // -------------------------------------------------------
+    // Move enumeration
+
+    #[test]
+    fn test_legal_moves() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "tc", "ad");
+        insert_card(g, "ml", "2h");
+        insert_card(g, "bl", "ah");
+        let moves = g.legal_moves();
+        let trashes = moves
+            .iter()
+            .filter(|(mv, _)| matches!(mv, Move::Trash(_)))
+            .count();
+        assert_eq!(trashes, 4);
+        let mut hands: Vec<_> = moves
+            .iter()
+            .filter_map(|(mv, ps)| match mv {
+                Move::PlayHand(h) => Some((*h, ps.len())),
+                Move::Trash(_) => None,
+            })
+            .collect();
+        hands.sort();
+        // Pairs: tl/bl, tc/bl. Three of a kind: tl/tc/bl.
+        assert_eq!(
+            hands,
+            vec![(Hand::Pair, 2), (Hand::Pair, 2), (Hand::ThreeOfAKind, 3)]
+        );
+        for (mv, ps) in moves {
+            g.selected = ps;
+            assert_eq!(g.selected_move(), Some(mv));
+        }
+    }
+
+    #[test]
+    fn test_no_trash_moves_without_trashes() {
+        let g = &mut Game::empty();
+        g.trashes = Trashes::None;
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "ks");
+        assert!(g.legal_moves().is_empty());
+    }
+
+    // -------------------------------------------------------
+    // Undo and redo
+
+    #[test]
+    fn test_undo_restores_played_hand() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ks");
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "ah");
+        g.trashes = Trashes::One;
+        g.select("tl bl");
+        g.checkpoint();
+        g.play_hand(Hand::Pair).unwrap();
+        assert_eq!(g.hand_score(), 2);
+        assert_eq!(g.trashes, Trashes::Two);
+
+        assert_eq!(g.undo(), Ok(()));
+        assert_eq!(g.hand_score(), 0);
+        assert_eq!(g.trashes, Trashes::One);
+        assert_eq!(g.spread.get_stack(p("tl")).len(), 2);
+        assert_eq!(g.selected_hand(), Some(Hand::Pair));
+        assert_eq!(g.undo(), Err(GameError::NothingToUndo));
+
+        assert_eq!(g.redo(), Ok(()));
+        assert_eq!(g.hand_score(), 2);
+        assert_eq!(g.cards_left(), 1);
+        assert_eq!(g.undos_used(), 1);
+    }
+
+    #[test]
+    fn test_undo_limit() {
+        let g = &mut Game::empty();
+        g.set_undo_limit(Some(0));
+        insert_card(g, "tl", "as");
+        g.checkpoint();
+        g.spend_one_trash().unwrap();
+        assert_eq!(g.undo(), Err(GameError::UndoLimit(0)));
+        assert_eq!(g.trashes, Trashes::One);
+    }
+
+    #[test]
+    fn test_errors_leave_game_alone() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "kh");
+        g.trashes = Trashes::None;
+        assert_eq!(g.spend_one_trash(), Err(GameError::NoTrashes));
+        assert_eq!(g.check_move(), Err(GameError::NothingSelected));
+        g.select("tl");
+        assert_eq!(g.check_move(), Err(GameError::NoTrashes));
+        g.select("tl bl");
+        assert_eq!(
+            g.check_move(),
+            Err(GameError::NotAHand(Unplayable::RanksDiffer))
+        );
+        assert_eq!(
+            g.play_hand(Hand::Pair),
+            Err(GameError::NotThatHand(Hand::Pair))
+        );
+        g.select("tl mc");
+        assert_eq!(g.check_move(), Err(GameError::EmptyStack(p("mc"))));
+        assert_eq!(g.play_hand(Hand::Pair), Err(GameError::EmptyStack(p("mc"))));
+        assert_eq!(g.hand_at(&g.selected), None);
+        assert_eq!(g.redo(), Err(GameError::NothingToRedo));
+        assert_eq!(g.cards_left(), 2);
+        assert_eq!(g.hand_score(), 0);
+    }
+
+    #[test]
+    fn test_over_selection() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ah");
+        insert_card(g, "tc", "2h");
+        insert_card(g, "tr", "3h");
+        insert_card(g, "ml", "4h");
+        insert_card(g, "mc", "5h");
+        insert_card(g, "mr", "ks");
+        insert_card(g, "bl", "5d");
+        g.select("tl tr tc ml mr mc");
+        assert!(g.selected_hand().is_none());
+    }
+
+    #[test]
+    fn test_explain_selection() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ks");
+        insert_card(g, "tc", "kh");
+        insert_card(g, "tr", "2d");
+        insert_card(g, "ml", "ac");
+        insert_card(g, "mc", "5c");
+        insert_card(g, "mr", "7s");
+        insert_card(g, "bl", "kd");
+        g.select("tl");
+        assert_eq!(g.explain_selection(), None);
+        g.select("tl tc");
+        assert_eq!(
+            g.explain_selection(),
+            Some(Unplayable::TooFewRows {
+                spanned: 1,
+                needed: 2
+            })
+        );
+        g.select("tl bl");
+        assert_eq!(g.explain_selection(), None);
+        g.select("tl tr");
+        assert_eq!(g.explain_selection(), Some(Unplayable::RanksDiffer));
+        g.select("tl tr ml");
+        assert_eq!(g.explain_selection(), Some(Unplayable::WrapsAround));
+        g.select("tl tr ml bl");
+        assert_eq!(g.explain_selection(), Some(Unplayable::RanksDiffer));
+        g.select("tl mc bl");
+        assert_eq!(g.explain_selection(), Some(Unplayable::NotConsecutive));
+        g.select("tl tc tr ml mc");
+        assert_eq!(g.explain_selection(), Some(Unplayable::NoFiveCardHand));
+        g.select("tl tc tr ml mc mr");
+        assert_eq!(g.explain_selection(), Some(Unplayable::WrongCount(6)));
+        g.select("tl bc");
+        assert_eq!(g.explain_selection(), Some(Unplayable::EmptyStack(p("bc"))));
+        g.trashes = Trashes::None;
+        g.select("mr");
+        assert_eq!(g.explain_selection(), Some(Unplayable::NoTrashes));
+        g.selected.clear();
+        assert_eq!(g.explain_selection(), None);
+    }
+
+    #[test]
+    fn test_explain_four_in_a_row() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "qs");
+        insert_card(g, "tc", "kh");
+        insert_card(g, "ml", "ad");
+        insert_card(g, "bl", "2c");
+        insert_card(g, "mc", "jc");
+        g.select("tl tc ml bl");
+        assert_eq!(g.explain_selection(), Some(Unplayable::WrongCount(4)));
+        g.select("mc tl tc ml");
+        assert_eq!(g.explain_selection(), Some(Unplayable::WrongCount(4)));
+        g.select("tl tc ml");
+        assert_eq!(g.selected_hand(), Some(Hand::StraightThree));
+    }
+
+    #[test]
+    fn test_preview_matches_score() {
+        let g = &mut Game::empty();
+        g.bonus_card = c("kh");
+        insert_card(g, "tl", "4c");
+        insert_card(g, "tl", "2h");
+        insert_card(g, "tc", "9d");
+        insert_card(g, "bl", "2s");
+        g.select("tl bl");
+        let preview = g.preview_move().unwrap();
+        assert_eq!(preview.base, 1);
+        assert_eq!(preview.multiplier, 2);
+        assert_eq!(preview.cleared, vec![(p("bl"), 5)]);
+        assert_eq!(preview.total(), 7);
+        g.select("tc");
+        assert_eq!(g.preview_move().unwrap().total(), 15);
+        g.select("tc bl");
+        assert_eq!(g.preview_move(), None);
+
+        let mut g = Game::generate_with_seed(11);
+        while let Some((_, positions)) = g.legal_moves().pop() {
+            g.selected = positions;
+            let expected = g.preview_move().unwrap().total();
+            let before = g.score();
+            crate::update::update(Msg::MakeMove, &mut g).unwrap();
+            assert_eq!(g.score() - before, expected);
+        }
+    }
+
+    #[test]
+    fn test_discards() {
+        let g = &mut Game::empty();
+        insert_card(g, "tl", "ks");
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "ah");
+        g.select("tl bl");
+        g.play_hand(Hand::Pair).unwrap();
+        g.trash(p("tl")).unwrap();
+        let pair = Move::PlayHand(Hand::Pair);
+        assert_eq!(
+            g.discards(),
+            &[
+                Discard {
+                    card: c("as"),
+                    cause: pair,
+                    turn: 1
+                },
+                Discard {
+                    card: c("ah"),
+                    cause: pair,
+                    turn: 1
+                },
+                Discard {
+                    card: c("ks"),
+                    cause: Move::Trash(p("tl")),
+                    turn: 2
+                },
+            ]
+        );
+        assert_eq!(g.trash(p("tl")), Err(GameError::EmptyStack(p("tl"))));
+    }
+
+    #[test]
+    fn test_cards_are_conserved() {
+        use crate::update::update;
+        let mut g = Game::generate_with_seed(11);
+        assert_eq!(g.check_conservation(), Ok(()));
+        let mut turns = 0;
+        while let Some((_, positions)) = g.legal_moves().pop() {
+            g.selected = positions;
+            update(Msg::MakeMove, &mut g).unwrap();
+            turns += 1;
+            assert_eq!(g.check_conservation(), Ok(()));
+        }
+        assert_eq!(g.discards().last().map(|d| d.turn), Some(turns));
+        assert_eq!(g.discards().len() + g.cards_left(), 51);
+
+        let ended = g.discards().to_vec();
+        update(Msg::Undo, &mut g).unwrap();
+        assert!(g.discards().len() < ended.len());
+        assert_eq!(g.check_conservation(), Ok(()));
+        update(Msg::Redo, &mut g).unwrap();
+        assert_eq!(g.discards(), ended.as_slice());
+
+        let bonus = g.bonus_card;
+        g.spread.tl.push(bonus);
+        let lost = g.discards[0].card;
+        g.discards.remove(0);
+        assert_eq!(
+            g.check_conservation(),
+            Err(Unaccounted {
+                missing: vec![lost],
+                duplicated: vec![bonus],
+            })
+        );
+    }
+
+    #[test]
+    fn test_rule_variants() {
+        let g = &mut Game::empty();
+        g.rules = Ruleset::three_rows();
+        insert_card(g, "tl", "as");
+        insert_card(g, "bl", "ah");
+        insert_card(g, "mc", "ad");
+        g.select("tl bl");
+        assert_eq!(g.selected_hand(), None);
+        g.select("tl mc bl");
+        assert_eq!(g.selected_hand(), Some(Hand::ThreeOfAKind));
+
+        g.rules = Ruleset::no_trash();
+        g.trashes = Trashes::None;
+        g.select("tl bl");
+        g.play_hand(Hand::Pair).unwrap();
+        assert_eq!(g.trashes, Trashes::None);
+        g.select("mc");
+        assert_eq!(g.selected_move(), None);
+    }
+}
+
+fn new_deck() -> Vec<Card> {
+    let mut deck = Vec::new();
+    for &r in RANKS.iter() {
+        for &s in SUITS.iter() {
+            deck.push(Card::new(r, s));
+        }
+    }
+    deck
+}
+
+#[test]
+fn test_deck_size() {
+    let deck = new_deck();
+    assert!(deck.len() == 52);
+}
+
+/// Seeds are kept to 32 bits so they're easy to read out and share.
+fn random_seed() -> u64 {
+    u64::from(rand::random::<u32>())
+}
+
+fn deal(seed: u64, pile_sizes: &[usize; 9]) -> Result<(Spread, Card), GameError> {
+    let mut spread = Spread::empty();
+    let mut d = new_deck();
+    shuffle(&mut d, seed);
+    for ((pos, _), cnt) in PILE_SIZES.iter().zip(pile_sizes.iter()) {
+        let hand = draw(&mut d, *cnt)?;
+        let stack: &mut Vec<_> = spread.get_stack_mut(*pos);
+        stack.extend(hand);
+    }
+    let bonus_card = d.pop().ok_or(GameError::Overdraw { wanted: 1, left: 0 })?;
+    if !d.is_empty() {
+        return Err(GameError::Underdeal(d.len()));
+    }
+    Ok((spread, bonus_card))
+}
+
+/// SplitMix64. Deals have to come out the same on every platform and with
+/// every version of `rand`, so shuffling doesn't go through `rand` at all.
+pub struct DealRng(u64);
+
+impl DealRng {
+    pub fn new(seed: u64) -> Self {
+        DealRng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "Can't pick a number below zero");
+        // Reject the short final run of values so every result is equally likely.
+        let zone = u64::MAX - (u64::MAX % n);
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+}
+
+fn shuffle(deck: &mut [Card], seed: u64) {
+    let mut rng = DealRng::new(seed);
+    for idx in (1..deck.len()).rev() {
+        let other = rng.below(idx as u64 + 1) as usize;
+        deck.swap(idx, other);
+    }
+}
+
+#[test]
+fn test_seeded_deals_repeat() {
+    let a = Game::generate_with_seed(1234);
+    let b = Game::generate_with_seed(1234);
+    for (pos, size) in &PILE_SIZES {
+        assert_eq!(a.spread.get_stack(*pos), b.spread.get_stack(*pos));
+        assert_eq!(a.spread.get_stack(*pos).len(), *size);
+    }
+    assert_eq!(a.bonus_card, b.bonus_card);
+    let c = Game::generate_with_seed(1235);
+    assert_ne!(a.spread.tl, c.spread.tl);
+}
+
+#[test]
+fn test_seeded_deal_is_stable() {
+    // If this changes, every shared seed deals a different game.
+    let g = Game::generate_with_seed(42);
+    assert_eq!(g.bonus_card, Card(Rank::Two, Suit::Heart));
+    assert_eq!(g.spread.tl.last(), Some(&Card(Rank::Six, Suit::Spade)));
+}
+
+#[test]
+fn test_restart_custom_deal() {
+    let src = Game::generate_with_seed(9).to_notation();
+    let mut g = Game::from_notation(&src).unwrap();
+    assert!(g.is_custom_deal());
+    g.spread.tl.pop();
+    g.restart().unwrap();
+    assert_eq!(g.to_notation(), src);
+    assert_eq!(g.spread.tl.len(), 8);
+    g.reset_with_seed(9).unwrap();
+    assert!(!g.is_custom_deal());
+
+    // A deal that doesn't fit the rules is turned down, not swapped for the
+    // seed's
+    let g = Game::from_notation(&src).unwrap();
+    let mut rules = Ruleset::standard();
+    rules.pile_sizes.swap(0, 8);
+    assert_eq!(
+        g.with_rules(rules).err(),
+        Some(GameError::BadDeal(ParseError::WrongSize {
+            stack: Position(RowId::Top, ColumnId::Left),
+            size: 8,
+            wanted: 2,
+        }))
+    );
+}
+
+#[test]
+fn test_with_rules() {
+    let g = Game::generate_with_seed(7)
+        .with_rules(Ruleset::no_trash())
+        .unwrap();
+    assert_eq!(g.trashes, Trashes::None);
+    assert_eq!(g.to_notation(), Game::generate_with_seed(7).to_notation());
+    assert_eq!(g.rules().name, "no-trash");
+
+    let mut too_many = Ruleset::standard();
+    too_many.pile_sizes[0] = 10;
+    assert_eq!(
+        Game::generate_with_seed(7).with_rules(too_many).err(),
+        Some(GameError::Overdraw { wanted: 2, left: 1 })
+    );
+    let mut too_few = Ruleset::standard();
+    too_few.pile_sizes[8] = 0;
+    assert_eq!(
+        Game::generate_with_seed(7).with_rules(too_few).err(),
+        Some(GameError::Underdeal(2))
+    );
+}
+
+#[test]
+fn test_restart() {
+    let mut g = Game::generate_with_seed(7);
+    let top = g.spread.tl.clone();
+    g.spread.tl.pop();
+    g.trashes = Trashes::None;
+    g.restart().unwrap();
+    assert_eq!(g.spread.tl, top);
+    assert_eq!(g.trashes, Trashes::Two);
+    assert_eq!(g.seed(), 7);
+}
+
+fn draw(deck: &mut Vec<Card>, cards: usize) -> Result<Vec<Card>, GameError> {
+    if deck.len() < cards {
+        return Err(GameError::Overdraw {
+            wanted: cards,
+            left: deck.len(),
+        });
+    }
+    let mut hand = Vec::new();
+    for _ in 0..cards {
+        hand.extend(deck.pop());
+    }
+    Ok(hand)
+}
+
+#[test]
+fn test_drawing() {
+    for handsize in 0..52 {
+        let mut deck = new_deck();
+        let orig_len = deck.len();
+        let hand = draw(&mut deck, handsize).unwrap();
+        assert!(hand.len() == handsize);
+        assert!(hand.len() + deck.len() == orig_len);
+    }
+    let mut deck = new_deck();
+    assert_eq!(
+        draw(&mut deck, 53),
+        Err(GameError::Overdraw {
+            wanted: 53,
+            left: 52
+        })
+    );
+    assert_eq!(deck.len(), 52);
+}
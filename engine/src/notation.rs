@@ -0,0 +1,275 @@
+/// A compact text form for a whole deal, so one can be written down or
+/// pasted in.
+///
+/// Each card is a rank letter (`a234567890jqk`, with `0` for ten) followed
+/// by a suit letter (`cdhs`). The nine stacks come in `PILE_SIZES` order,
+/// each from the bottom card up, with the bonus card last, all separated by
+/// slashes:
+///
+/// ```text
+/// 4h9c.../.../.../.../.../.../.../.../qdks/2h
+/// ```
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::model::*;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    /// There should be ten parts: nine stacks and the bonus card.
+    WrongParts(usize),
+    BadCard(String),
+    BadBonus(String),
+    Duplicate(Card),
+    Missing(Vec<Card>),
+    /// A stack doesn't have as many cards as the rules deal it.
+    WrongSize {
+        stack: Position,
+        size: usize,
+        wanted: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::WrongParts(n) => write!(
+                f,
+                "A deal has nine stacks and a bonus card separated by '/', not {} parts",
+                n
+            ),
+            ParseError::BadCard(src) => write!(f, "'{}' isn't a card", src),
+            ParseError::BadBonus(src) => write!(f, "The bonus card '{}' should be one card", src),
+            ParseError::Duplicate(c) => write!(f, "{} is in the deal twice", c.to_string().trim()),
+            ParseError::Missing(cards) => {
+                let names: Vec<String> = cards
+                    .iter()
+                    .map(|c| c.to_string().trim().to_owned())
+                    .collect();
+                write!(f, "The deal is missing {}", names.join(" "))
+            }
+            ParseError::WrongSize {
+                stack,
+                size,
+                wanted,
+            } => write!(
+                f,
+                "Stack {} has {} cards where the rules deal {}",
+                stack, size, wanted
+            ),
+        }
+    }
+}
+
+const RANK_LETTERS: &str = "a234567890jqk";
+const SUIT_LETTERS: &str = "cdhs";
+
+/// Read one card, e.g. `0h` for the ten of hearts.
+pub fn parse_card(src: &str) -> Option<Card> {
+    let mut chars = src.chars();
+    let r = RANK_LETTERS.find(chars.next()?.to_ascii_lowercase())?;
+    let s = SUIT_LETTERS.find(chars.next()?.to_ascii_lowercase())?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(Card(RANKS[r], SUITS[s]))
+}
+
+pub fn card_notation(Card(r, s): Card) -> String {
+    let rank = RANK_LETTERS.as_bytes()[r as usize] as char;
+    let suit = SUIT_LETTERS.as_bytes()[s as usize] as char;
+    format!("{}{}", rank, suit)
+}
+
+fn parse_cards(src: &str) -> Result<Vec<Card>, ParseError> {
+    let chars: Vec<char> = src.chars().collect();
+    chars
+        .chunks(2)
+        .map(|pair| {
+            let card: String = pair.iter().collect();
+            parse_card(&card).ok_or(ParseError::BadCard(card))
+        })
+        .collect()
+}
+
+fn parse_stacks(parts: &[&str]) -> Result<Spread, ParseError> {
+    let mut spread = Spread::empty();
+    for ((pos, _), part) in PILE_SIZES.iter().zip(parts.iter()) {
+        *spread.get_stack_mut(*pos) = parse_cards(part)?;
+    }
+    Ok(spread)
+}
+
+/// Read just the nine stacks, with no bonus card and any cards at all, to
+/// set up a position by hand: `5c//////5h//` is a five of clubs top left
+/// and a five of hearts bottom left.
+pub fn parse_spread(src: &str) -> Result<Spread, ParseError> {
+    let parts: Vec<&str> = src.trim().split('/').map(str::trim).collect();
+    if parts.len() != PILE_SIZES.len() {
+        return Err(ParseError::WrongParts(parts.len()));
+    }
+    parse_stacks(&parts)
+}
+
+/// Read a deal, checking that every card is dealt exactly once.
+pub fn parse(src: &str) -> Result<(Spread, Card), ParseError> {
+    let parts: Vec<&str> = src.trim().split('/').map(str::trim).collect();
+    if parts.len() != PILE_SIZES.len() + 1 {
+        return Err(ParseError::WrongParts(parts.len()));
+    }
+    let spread = parse_stacks(&parts[..PILE_SIZES.len()])?;
+    let bonus_src = parts[PILE_SIZES.len()];
+    let bonus_card = match parse_cards(bonus_src)?.as_slice() {
+        [card] => *card,
+        _ => return Err(ParseError::BadBonus(bonus_src.to_owned())),
+    };
+
+    let mut seen = HashSet::new();
+    let dealt = PILE_SIZES
+        .iter()
+        .flat_map(|(pos, _)| spread.get_stack(*pos).iter())
+        .chain(std::iter::once(&bonus_card));
+    for card in dealt {
+        if !seen.insert(*card) {
+            return Err(ParseError::Duplicate(*card));
+        }
+    }
+    let missing: Vec<Card> = RANKS
+        .iter()
+        .flat_map(|&r| SUITS.iter().map(move |&s| Card(r, s)))
+        .filter(|c| !seen.contains(c))
+        .collect();
+    if !missing.is_empty() {
+        return Err(ParseError::Missing(missing));
+    }
+    Ok((spread, bonus_card))
+}
+
+/// Check that each stack has as many cards as `pile_sizes` deals it.
+pub fn check_sizes(spread: &Spread, pile_sizes: &[usize; 9]) -> Result<(), ParseError> {
+    for ((pos, _), &wanted) in PILE_SIZES.iter().zip(pile_sizes.iter()) {
+        let size = spread.get_stack(*pos).len();
+        if size != wanted {
+            return Err(ParseError::WrongSize {
+                stack: *pos,
+                size,
+                wanted,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn write(spread: &Spread, bonus_card: Card) -> String {
+    let mut parts: Vec<String> = PILE_SIZES
+        .iter()
+        .map(|(pos, _)| {
+            spread
+                .get_stack(*pos)
+                .iter()
+                .map(|c| card_notation(*c))
+                .collect()
+        })
+        .collect();
+    parts.push(card_notation(bonus_card));
+    parts.join("/")
+}
+
+#[test]
+fn test_round_trip() {
+    let g = Game::generate_with_seed(42);
+    let src = g.to_notation();
+    assert_eq!(src.len(), 52 * 2 + 9);
+    let parsed = Game::from_notation(&src).unwrap();
+    for (pos, _) in PILE_SIZES.iter() {
+        assert_eq!(parsed.spread.get_stack(*pos), g.spread.get_stack(*pos));
+    }
+    assert_eq!(parsed.bonus_card, g.bonus_card);
+    assert_eq!(parsed.to_notation(), src);
+    assert_eq!(
+        Game::from_notation(&src.to_uppercase())
+            .unwrap()
+            .to_notation(),
+        src
+    );
+}
+
+#[test]
+fn test_cards() {
+    assert_eq!(parse_card("0h"), Some(Card(Rank::Ten, Suit::Heart)));
+    assert_eq!(parse_card("As"), Some(Card(Rank::Ace, Suit::Spade)));
+    assert_eq!(parse_card("1h"), None);
+    assert_eq!(parse_card("qx"), None);
+    assert_eq!(parse_card("kdd"), None);
+    for &r in RANKS.iter() {
+        for &s in SUITS.iter() {
+            assert_eq!(parse_card(&card_notation(Card(r, s))), Some(Card(r, s)));
+        }
+    }
+}
+
+#[test]
+fn test_parse_spread() {
+    let spread = parse_spread("5c2h//////5H//").unwrap();
+    assert_eq!(
+        spread.tl,
+        vec![Card(Rank::Five, Suit::Club), Card(Rank::Two, Suit::Heart)]
+    );
+    assert_eq!(spread.bl, vec![Card(Rank::Five, Suit::Heart)]);
+    assert!(spread.tc.is_empty() && spread.br.is_empty());
+    assert_eq!(parse_spread("5c/5h").err(), Some(ParseError::WrongParts(2)));
+}
+
+#[test]
+fn test_bad_deals() {
+    let src = Game::generate_with_seed(1).to_notation();
+    let parts: Vec<&str> = src.split('/').collect();
+    assert_eq!(
+        Game::from_notation(&parts[..9].join("/")).err(),
+        Some(ParseError::WrongParts(9))
+    );
+
+    // Swap the bonus card for the top of the first stack's
+    let first_top = &parts[0][parts[0].len() - 2..];
+    let mut doubled = parts.clone();
+    let bonus = doubled.pop().unwrap();
+    doubled.push(first_top);
+    assert_eq!(
+        Game::from_notation(&doubled.join("/")).err(),
+        Some(ParseError::Duplicate(parse_card(first_top).unwrap()))
+    );
+    let mut short = parts.clone();
+    short[1] = "";
+    assert!(matches!(
+        Game::from_notation(&short.join("/")),
+        Err(ParseError::Missing(cards)) if cards.len() == 8
+    ));
+    let mut two_bonus = parts.clone();
+    let both = format!("{}{}", bonus, first_top);
+    two_bonus[9] = &both;
+    assert_eq!(
+        Game::from_notation(&two_bonus.join("/")).err(),
+        Some(ParseError::BadBonus(both.clone()))
+    );
+    let mut garbled = parts.clone();
+    garbled[2] = "zz";
+    assert_eq!(
+        Game::from_notation(&garbled.join("/")).err(),
+        Some(ParseError::BadCard("zz".to_owned()))
+    );
+
+    // Move the top card of the first stack onto the second
+    let mut moved = parts;
+    let first = moved[0][..moved[0].len() - 2].to_owned();
+    let second = format!("{}{}", moved[1], first_top);
+    moved[0] = &first;
+    moved[1] = &second;
+    assert_eq!(
+        Game::from_notation(&moved.join("/")).err(),
+        Some(ParseError::WrongSize {
+            stack: Position(RowId::Top, ColumnId::Left),
+            size: 7,
+            wanted: 8,
+        })
+    );
+}
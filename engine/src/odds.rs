@@ -0,0 +1,416 @@
+/// Card counting: how likely each kind of hand is to still turn up, and what
+/// could be hidden in each stack.
+///
+/// A hand can still be played if its cards are somewhere in the spread, in
+/// different stacks, and over enough rows; digging down to them is assumed
+/// to be possible. Where the hidden cards are isn't known, so the chance is
+/// the fraction of random deals consistent with what's visible (see
+/// `View::sample`) in which the hand can be played.
+use crate::model::*;
+use crate::rules::Ruleset;
+use crate::strategy::View;
+
+/// How many deals to try. Always the same ones, so the numbers only change
+/// when the game does.
+const SAMPLES: usize = 100;
+
+/// The hands in the order they're shown.
+pub const HANDS: [Hand; 8] = [
+    Hand::Pair,
+    Hand::StraightThree,
+    Hand::ThreeOfAKind,
+    Hand::StraightFive,
+    Hand::FullHouse,
+    Hand::Flush,
+    Hand::FourOfAKind,
+    Hand::StraightFlush,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chance {
+    /// Between 0 and 1.
+    pub p: f64,
+    /// Not enough of the cards are left for this hand to ever be played,
+    /// wherever the hidden ones are.
+    pub dead: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Odds {
+    /// Indexed like `HANDS`.
+    pub hands: [Chance; 8],
+    /// Pairs, threes and fours of each rank, indexed like `RANKS`.
+    pub of_a_kind: [[Chance; 3]; 13],
+    /// Flushes in each suit, indexed like `SUITS`.
+    pub flushes: [Chance; 4],
+}
+
+/// The stack and row a card is in.
+type Spot = (usize, usize);
+
+/// Where each remaining card is, grouped by rank and by suit.
+struct Layout {
+    by_rank: [Vec<(Spot, Suit)>; 13],
+    by_suit: [Vec<(Spot, Rank)>; 4],
+}
+
+impl Layout {
+    fn new(cards: impl Iterator<Item = (Spot, Card)>) -> Self {
+        let mut layout = Layout {
+            by_rank: Default::default(),
+            by_suit: Default::default(),
+        };
+        for (spot, Card(r, s)) in cards {
+            layout.by_rank[r as usize].push((spot, s));
+            layout.by_suit[s as usize].push((spot, r));
+        }
+        layout
+    }
+
+    /// Stacks are numbered in `PILE_SIZES` order.
+    fn of(g: &Game) -> Self {
+        Layout::new(PILE_SIZES.iter().enumerate().flat_map(|(idx, (pos, _))| {
+            let Position(rowid, _) = pos;
+            let row = *rowid as usize;
+            g.spread
+                .get_stack(*pos)
+                .iter()
+                .map(move |c| ((idx, row), *c))
+        }))
+    }
+
+    /// Every card in a stack and row of its own, so the only thing that can
+    /// stop a hand is the cards not being there.
+    fn unconstrained(cards: impl Iterator<Item = Card>) -> Self {
+        Layout::new(cards.enumerate().map(|(idx, c)| ((idx, idx), c)))
+    }
+}
+
+/// Whether cards in these spots could all be on top at once, and span
+/// enough rows to be played under `rules`.
+fn fits(spots: &[Spot], rules: &Ruleset) -> bool {
+    let mut stacks = 0u64;
+    let mut rows = 0u64;
+    for &(stack, row) in spots {
+        if stacks & (1 << stack) != 0 {
+            return false;
+        }
+        stacks |= 1 << stack;
+        rows |= 1 << row;
+    }
+    rows.count_ones() as usize >= rules.min_rows
+}
+
+/// Every way of choosing `n` of `len` items, as bitmasks.
+fn choices(len: usize, n: u32) -> impl Iterator<Item = u16> {
+    (0u16..(1 << len)).filter(move |m| m.count_ones() == n)
+}
+
+fn picked<T: Copy>(items: &[T], mask: u16) -> impl Iterator<Item = T> + '_ {
+    (0..items.len())
+        .filter(move |idx| mask & (1 << idx) != 0)
+        .map(move |idx| items[idx])
+}
+
+/// Ranks in straight order, with the ace at both ends.
+fn rank_at(idx: usize) -> usize {
+    idx % 13
+}
+
+fn is_straight(ranks: &[Rank]) -> bool {
+    let len = ranks.len();
+    (0..=14 - len).any(|start| {
+        (start..start + len).all(|idx| ranks.iter().any(|&r| r as usize == rank_at(idx)))
+    })
+}
+
+/// Pick one card from each group, looking for a choice that fits.
+fn any_pick(
+    groups: &[&[(Spot, Suit)]],
+    stacks: &mut Vec<Spot>,
+    suit: Option<Suit>,
+    rules: &Ruleset,
+) -> bool {
+    let (group, rest) = match groups.split_first() {
+        Some(split) => split,
+        None => return fits(stacks, rules),
+    };
+    for &(stack, s) in group.iter() {
+        if matches!(suit, Some(suit) if suit != s) {
+            continue;
+        }
+        stacks.push(stack);
+        let found = any_pick(rest, stacks, suit, rules);
+        stacks.pop();
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+fn of_a_kind(layout: &Layout, rank: usize, n: u32, rules: &Ruleset) -> bool {
+    let cards = &layout.by_rank[rank];
+    choices(cards.len(), n)
+        .any(|m| fits(&picked(cards, m).map(|(s, _)| s).collect::<Vec<_>>(), rules))
+}
+
+fn straight(layout: &Layout, len: usize, suit: Option<Suit>, rules: &Ruleset) -> bool {
+    (0..=14 - len).any(|start| {
+        let groups: Vec<&[(Spot, Suit)]> = (start..start + len)
+            .map(|idx| layout.by_rank[rank_at(idx)].as_slice())
+            .collect();
+        any_pick(&groups, &mut Vec::new(), suit, rules)
+    })
+}
+
+fn flush(layout: &Layout, suit: usize, rules: &Ruleset) -> bool {
+    let cards = &layout.by_suit[suit];
+    choices(cards.len(), 5).any(|m| {
+        let stacks: Vec<Spot> = picked(cards, m).map(|(s, _)| s).collect();
+        let ranks: Vec<Rank> = picked(cards, m).map(|(_, r)| r).collect();
+        fits(&stacks, rules) && !is_straight(&ranks)
+    })
+}
+
+fn full_house(layout: &Layout, rules: &Ruleset) -> bool {
+    (0..13).any(|three| {
+        let threes = &layout.by_rank[three];
+        (0..13).filter(|&two| two != three).any(|two| {
+            let twos = &layout.by_rank[two];
+            choices(threes.len(), 3).any(|t| {
+                choices(twos.len(), 2).any(|p| {
+                    let stacks: Vec<Spot> = picked(threes, t)
+                        .chain(picked(twos, p))
+                        .map(|(s, _)| s)
+                        .collect();
+                    fits(&stacks, rules)
+                })
+            })
+        })
+    })
+}
+
+/// What can still be played in one particular deal: each hand in `HANDS`,
+/// then of-a-kinds by rank, then flushes by suit.
+fn playable(layout: &Layout, rules: &Ruleset) -> ([bool; 8], [[bool; 3]; 13], [bool; 4]) {
+    let mut kinds = [[false; 3]; 13];
+    for (rank, k) in kinds.iter_mut().enumerate() {
+        for (n, playable) in (2..).zip(k.iter_mut()) {
+            *playable = of_a_kind(layout, rank, n, rules);
+        }
+    }
+    let mut flushes = [false; 4];
+    for (suit, f) in flushes.iter_mut().enumerate() {
+        *f = flush(layout, suit, rules);
+    }
+    let hands = [
+        kinds.iter().any(|k| k[0]),
+        straight(layout, 3, None, rules),
+        kinds.iter().any(|k| k[1]),
+        straight(layout, 5, None, rules),
+        full_house(layout, rules),
+        flushes.iter().any(|&f| f),
+        kinds.iter().any(|k| k[2]),
+        SUITS.iter().any(|&s| straight(layout, 5, Some(s), rules)),
+    ];
+    (hands, kinds, flushes)
+}
+
+/// Work out the odds for `game` from what a player can see of it.
+pub fn compute(game: &Game) -> Odds {
+    let view = View::of(game);
+    let rules = game.rules();
+    let remaining = view.remaining_cards().into_iter();
+    let (live_hands, live_kinds, live_flushes) = playable(&Layout::unconstrained(remaining), rules);
+
+    let mut hands = [0; 8];
+    let mut kinds = [[0; 3]; 13];
+    let mut flushes = [0; 4];
+    let mut rng = DealRng::new(0);
+    for _ in 0..SAMPLES {
+        let (h, k, f) = playable(&Layout::of(&view.sample(&mut rng)), rules);
+        for (count, &ok) in hands.iter_mut().zip(h.iter()) {
+            *count += ok as usize;
+        }
+        for (counts, ok) in kinds.iter_mut().zip(k.iter()) {
+            for (count, &ok) in counts.iter_mut().zip(ok.iter()) {
+                *count += ok as usize;
+            }
+        }
+        for (count, &ok) in flushes.iter_mut().zip(f.iter()) {
+            *count += ok as usize;
+        }
+    }
+
+    let chance = |count: usize, live: bool| Chance {
+        p: count as f64 / SAMPLES as f64,
+        dead: !live,
+    };
+    let mut odds = Odds {
+        hands: [chance(0, false); 8],
+        of_a_kind: [[chance(0, false); 3]; 13],
+        flushes: [chance(0, false); 4],
+    };
+    for idx in 0..8 {
+        odds.hands[idx] = chance(hands[idx], live_hands[idx]);
+    }
+    for rank in 0..13 {
+        for n in 0..3 {
+            odds.of_a_kind[rank][n] = chance(kinds[rank][n], live_kinds[rank][n]);
+        }
+    }
+    for suit in 0..4 {
+        odds.flushes[suit] = chance(flushes[suit], live_flushes[suit]);
+    }
+    odds
+}
+
+// -----------------------------------
+// Hidden cards
+
+/// What could be under the top card of one stack.
+///
+/// Every card that hasn't been seen is equally likely to be in any hidden
+/// place, so each one has the same chance of being in this stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Buried {
+    /// How many cards are hidden under the top card.
+    pub hidden: usize,
+    /// The cards that haven't been seen, any of which could be in there.
+    pub unseen: Vec<Card>,
+    /// The chance that any one unseen card is in there.
+    pub each: f64,
+    /// The chance of at least one card of each rank being in there, indexed
+    /// like `RANKS`.
+    pub by_rank: [f64; 13],
+    /// How many cards of each suit there are likely to be, indexed like
+    /// `SUITS`.
+    pub by_suit: [f64; 4],
+}
+
+/// Work out what could be under the top card at `pos`.
+pub fn buried(view: &View, pos: Position) -> Buried {
+    let tops: Vec<Card> = PILE_SIZES
+        .iter()
+        .filter_map(|(p, _)| view.top(*p))
+        .collect();
+    let mut unseen: Vec<Card> = view
+        .remaining_cards()
+        .into_iter()
+        .filter(|c| !tops.contains(c))
+        .collect();
+    unseen.sort();
+    let hidden = view.size(pos).saturating_sub(1);
+    let total = unseen.len();
+    let each = if total == 0 {
+        0.0
+    } else {
+        hidden as f64 / total as f64
+    };
+
+    // The chance that none of `n` particular cards are among `hidden` drawn
+    // from all the unseen ones
+    let none_of = |n: usize| -> f64 {
+        (0..hidden)
+            .map(|i| (total - n).saturating_sub(i) as f64 / (total - i) as f64)
+            .product()
+    };
+    let mut by_rank = [0.0; 13];
+    for (p, &rank) in by_rank.iter_mut().zip(RANKS.iter()) {
+        let n = unseen.iter().filter(|Card(r, _)| *r == rank).count();
+        *p = 1.0 - none_of(n);
+    }
+    let mut by_suit = [0.0; 4];
+    for (e, &suit) in by_suit.iter_mut().zip(SUITS.iter()) {
+        let n = unseen.iter().filter(|Card(_, s)| *s == suit).count();
+        *e = n as f64 * each;
+    }
+    Buried {
+        hidden,
+        unseen,
+        each,
+        by_rank,
+        by_suit,
+    }
+}
+
+#[test]
+fn test_fits() {
+    let rules = Ruleset::standard();
+    assert!(fits(&[(0, 0), (3, 1)], &rules));
+    assert!(!fits(&[(0, 0), (1, 0), (2, 0)], &rules));
+    assert!(!fits(&[(0, 0), (0, 0), (3, 1)], &rules));
+}
+
+#[test]
+fn test_three_rows() {
+    let rules = Ruleset::three_rows();
+    assert!(!fits(&[(0, 0), (3, 1)], &rules));
+    assert!(fits(&[(0, 0), (3, 1), (6, 2)], &rules));
+
+    // Every pair needs a third row, so none can be played
+    let g = Game::generate_with_seed(2).with_rules(rules).unwrap();
+    let odds = compute(&g);
+    assert!(odds.hands[0].dead);
+    assert_eq!(odds.hands[0].p, 0.0);
+    assert!(odds.of_a_kind.iter().all(|k| k[0].dead));
+    assert!(!odds.hands[1].dead);
+}
+
+#[test]
+fn test_fresh_deal_has_everything() {
+    let g = Game::generate_with_seed(2);
+    let odds = compute(&g);
+    assert!(odds.hands.iter().all(|c| !c.dead && c.p > 0.0));
+    assert_eq!(odds.hands[0].p, 1.0);
+    // Only the bonus card is missing, so its rank is the only one that can't
+    // make four of a kind.
+    let Card(bonus_rank, _) = g.bonus_card;
+    for (idx, kinds) in odds.of_a_kind.iter().enumerate() {
+        assert!(!kinds[0].dead && !kinds[1].dead);
+        assert_eq!(kinds[2].dead, idx == bonus_rank as usize);
+    }
+}
+
+#[test]
+fn test_dead_hands() {
+    let mut g = Game::generate_with_seed(0);
+    g.spread = Spread::empty();
+    // Two aces and the five to nine of hearts, spread over two rows
+    g.spread.tl = vec![Card(Rank::Ace, Suit::Club), Card(Rank::Five, Suit::Heart)];
+    g.spread.tc = vec![Card(Rank::Six, Suit::Heart)];
+    g.spread.tr = vec![Card(Rank::Seven, Suit::Heart)];
+    g.spread.ml = vec![Card(Rank::Ace, Suit::Spade), Card(Rank::Eight, Suit::Heart)];
+    g.spread.mc = vec![Card(Rank::Nine, Suit::Heart)];
+    let odds = compute(&g);
+    let ace = &odds.of_a_kind[Rank::Ace as usize];
+    assert!(!ace[0].dead);
+    assert!(ace[1].dead && ace[2].dead);
+    assert_eq!(ace[1].p, 0.0);
+    assert!(odds.of_a_kind[Rank::King as usize][0].dead);
+    assert!(!odds.hands[HANDS.len() - 1].dead);
+    assert!(odds.hands[HANDS.iter().position(|h| *h == Hand::Flush).unwrap()].dead);
+    assert!(odds.flushes[Suit::Club as usize].dead);
+}
+
+#[test]
+fn test_buried() {
+    let mut g = Game::generate_with_seed(4);
+    let tl = Position(RowId::Top, ColumnId::Left);
+    let b = buried(&View::of(&g), tl);
+    assert_eq!(b.hidden, 7);
+    // 51 cards dealt, 9 of them showing
+    assert_eq!(b.unseen.len(), 42);
+    assert!((b.each - 7.0 / 42.0).abs() < 1e-9);
+    let expected: f64 = b.by_suit.iter().sum();
+    assert!((expected - 7.0).abs() < 1e-9);
+    assert!(b.by_rank.iter().all(|p| *p > 0.0 && *p < 1.0));
+
+    // Once only the top card is left, nothing can be under it
+    g.spread.tl.drain(..7);
+    let b = buried(&View::of(&g), tl);
+    assert_eq!(b.hidden, 0);
+    assert_eq!(b.each, 0.0);
+    assert!(b.by_rank.iter().all(|p| *p == 0.0));
+}
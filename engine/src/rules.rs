@@ -0,0 +1,106 @@
+/// The rules a game is played under: what hands score, how the cards are
+/// dealt and when a hand can be played. Besides standard Sage there are a
+/// few built-in variants, picked with `--rules`.
+use serde::{Deserialize, Serialize};
+
+use crate::model::*;
+
+/// Names of the built-in rulesets, in the order they're listed.
+pub const PRESETS: [&str; 3] = ["standard", "no-trash", "three-rows"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ruleset {
+    pub name: String,
+    /// Base points for each hand, in `Hand` order.
+    pub hand_points: [u32; 8],
+    /// Points for clearing a stack in the top, middle and bottom rows.
+    pub row_bonuses: [u32; 3],
+    /// How many cards are dealt to each stack, in `PILE_SIZES` order. With
+    /// the bonus card these have to account for the whole deck.
+    pub pile_sizes: [usize; 9],
+    /// How many rows a hand's cards have to come from.
+    pub min_rows: usize,
+    /// Trashes at the start of the game, and the most that can be saved up.
+    pub max_trashes: Trashes,
+    /// What a hand with a card of the bonus suit is multiplied by.
+    pub bonus_multiplier: u32,
+}
+
+impl Ruleset {
+    /// Sage Solitaire as published.
+    pub fn standard() -> Ruleset {
+        let mut pile_sizes = [0; 9];
+        for (size, (_, standard)) in pile_sizes.iter_mut().zip(PILE_SIZES.iter()) {
+            *size = *standard;
+        }
+        Ruleset {
+            name: "standard".to_owned(),
+            hand_points: [1, 2, 3, 5, 7, 9, 10, 15],
+            row_bonuses: [15, 10, 5],
+            pile_sizes,
+            min_rows: 2,
+            max_trashes: Trashes::Two,
+            bonus_multiplier: 2,
+        }
+    }
+
+    /// No trashes at all: every card has to leave in a hand.
+    pub fn no_trash() -> Ruleset {
+        Ruleset {
+            name: "no-trash".to_owned(),
+            max_trashes: Trashes::None,
+            ..Ruleset::standard()
+        }
+    }
+
+    /// Hands have to take a card from every row, so pairs can't be played.
+    pub fn three_rows() -> Ruleset {
+        Ruleset {
+            name: "three-rows".to_owned(),
+            min_rows: 3,
+            ..Ruleset::standard()
+        }
+    }
+
+    /// Look up one of the `PRESETS` by name.
+    pub fn preset(name: &str) -> Option<Ruleset> {
+        match name {
+            "standard" => Some(Ruleset::standard()),
+            "no-trash" => Some(Ruleset::no_trash()),
+            "three-rows" => Some(Ruleset::three_rows()),
+            _ => None,
+        }
+    }
+
+    pub fn points(&self, h: Hand) -> u32 {
+        self.hand_points[h as usize]
+    }
+
+    pub fn row_bonus(&self, rowid: RowId) -> u32 {
+        self.row_bonuses[rowid as usize]
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Ruleset::standard()
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::standard()
+    }
+}
+
+#[test]
+fn test_presets() {
+    for name in PRESETS.iter() {
+        let rules = Ruleset::preset(name).unwrap();
+        assert_eq!(rules.name, *name);
+        assert_eq!(rules.pile_sizes.iter().sum::<usize>(), 51);
+        assert_eq!(rules.is_standard(), *name == "standard");
+    }
+    assert_eq!(Ruleset::preset("easy"), None);
+    let standard = Ruleset::standard();
+    assert_eq!(standard.points(Hand::FullHouse), 7);
+    assert_eq!(standard.row_bonus(RowId::Bottom), 5);
+}
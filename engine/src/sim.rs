@@ -0,0 +1,224 @@
+/// Playing many games with a strategy to see how well it does.
+///
+/// Each game's seed comes from the base seed, and each game's strategy is
+/// seeded from its game, so a run can be repeated exactly no matter how many
+/// threads it's spread across.
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::model::*;
+use crate::rules::Ruleset;
+use crate::strategy::{self, Greedy, MonteCarlo, Strategy, View};
+
+/// The strategies that can be simulated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrategyKind {
+    Greedy,
+    /// With this many sampled deals per move.
+    MonteCarlo(usize),
+}
+
+impl StrategyKind {
+    /// Read `greedy`, `montecarlo` or `montecarlo:SAMPLES`.
+    pub fn parse(src: &str) -> Option<StrategyKind> {
+        let mut parts = src.splitn(2, ':');
+        match (parts.next()?, parts.next()) {
+            ("greedy", None) => Some(StrategyKind::Greedy),
+            ("montecarlo", None) => Some(StrategyKind::MonteCarlo(20)),
+            ("montecarlo", Some(n)) => n.parse().ok().map(StrategyKind::MonteCarlo),
+            _ => None,
+        }
+    }
+
+    fn build(self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Greedy => Box::new(Greedy),
+            StrategyKind::MonteCarlo(samples) => Box::new(MonteCarlo::new(samples, seed)),
+        }
+    }
+}
+
+impl std::fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StrategyKind::Greedy => write!(f, "greedy"),
+            StrategyKind::MonteCarlo(samples) => write!(f, "montecarlo:{}", samples),
+        }
+    }
+}
+
+/// How one simulated game went.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: u32,
+    pub cleared: bool,
+    pub moves: usize,
+    pub hands: BTreeMap<Hand, u32>,
+    pub micros: u64,
+}
+
+/// The seeds of `n` games, starting from `base`. Like any other seed,
+/// they're kept to 32 bits.
+pub fn seeds(base: u64, n: usize) -> Vec<u64> {
+    let mut rng = DealRng::new(base);
+    (0..n).map(|_| rng.next_u64() >> 32).collect()
+}
+
+/// Play one game to the end. Fails if `rules` can't deal a game, or the
+/// strategy makes a move that isn't allowed.
+pub fn play(kind: StrategyKind, rules: &Ruleset, seed: u64) -> Result<GameResult, GameError> {
+    let started = Instant::now();
+    let mut g = Game::generate_with_seed(seed).with_rules(rules.clone())?;
+    let mut strategy = kind.build(seed);
+    let mut moves = 0;
+    let mut hands = BTreeMap::new();
+    while let Some(m) = strategy.choose(&View::of(&g)) {
+        if let (Move::PlayHand(h), _) = m {
+            *hands.entry(h).or_insert(0) += 1;
+        }
+        strategy::apply(&mut g, m)?;
+        moves += 1;
+    }
+    Ok(GameResult {
+        seed,
+        score: g.score(),
+        cleared: g.status() == Status::Won,
+        moves,
+        hands,
+        micros: started.elapsed().as_micros() as u64,
+    })
+}
+
+/// Play a game for each seed on `jobs` threads. Results come back in the
+/// same order as the seeds. Fails with the first game's error if any game
+/// does.
+pub fn run(
+    kind: StrategyKind,
+    rules: &Ruleset,
+    seeds: &[u64],
+    jobs: usize,
+) -> Result<Vec<GameResult>, GameError> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; seeds.len()]);
+    thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            s.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                if idx >= seeds.len() {
+                    break;
+                }
+                let result = play(kind, rules, seeds[idx]);
+                results.lock().expect("A simulation thread panicked")[idx] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .expect("A simulation thread panicked")
+        .into_iter()
+        .map(|r| r.expect("Every game was played"))
+        .collect()
+}
+
+/// Totals over a set of games.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Report {
+    pub games: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    /// The fraction of games where every card was cleared.
+    pub clear_rate: f64,
+    /// Average number of each hand played per game.
+    pub hands: BTreeMap<Hand, f64>,
+    pub mean_micros: f64,
+}
+
+impl Report {
+    pub fn of(results: &[GameResult]) -> Report {
+        let n = results.len().max(1) as f64;
+        let mean = results.iter().map(|r| f64::from(r.score)).sum::<f64>() / n;
+        let variance = results
+            .iter()
+            .map(|r| (f64::from(r.score) - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        let mut scores: Vec<u32> = results.iter().map(|r| r.score).collect();
+        scores.sort_unstable();
+        let median = match scores.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => f64::from(scores[len / 2]),
+            len => f64::from(scores[len / 2 - 1] + scores[len / 2]) / 2.0,
+        };
+        let mut hands = BTreeMap::new();
+        for r in results {
+            for (h, count) in r.hands.iter() {
+                *hands.entry(*h).or_insert(0.0) += f64::from(*count) / n;
+            }
+        }
+        Report {
+            games: results.len(),
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            clear_rate: results.iter().filter(|r| r.cleared).count() as f64 / n,
+            hands,
+            mean_micros: results.iter().map(|r| r.micros as f64).sum::<f64>() / n,
+        }
+    }
+}
+
+#[test]
+fn test_runs_repeat() {
+    let seeds = seeds(7, 6);
+    assert_eq!(seeds, self::seeds(7, 6));
+    assert!(seeds.iter().all(|s| *s <= u64::from(u32::MAX)));
+    let rules = Ruleset::standard();
+    let one = run(StrategyKind::Greedy, &rules, &seeds, 1).unwrap();
+    let three = run(StrategyKind::Greedy, &rules, &seeds, 3).unwrap();
+    let strip = |rs: Vec<GameResult>| -> Vec<(u64, u32, usize)> {
+        rs.into_iter().map(|r| (r.seed, r.score, r.moves)).collect()
+    };
+    assert_eq!(strip(one), strip(three));
+
+    let mut broken = rules.clone();
+    broken.pile_sizes[0] = 0;
+    assert_eq!(
+        run(StrategyKind::Greedy, &broken, &seeds, 2),
+        Err(GameError::Underdeal(8))
+    );
+}
+
+#[test]
+fn test_report() {
+    let result = |score, cleared| GameResult {
+        seed: 0,
+        score,
+        cleared,
+        moves: 10,
+        hands: [(Hand::Pair, 3)].iter().copied().collect(),
+        micros: 100,
+    };
+    let report = Report::of(&[result(10, false), result(30, true), result(20, false)]);
+    assert_eq!(report.mean, 20.0);
+    assert_eq!(report.median, 20.0);
+    assert!((report.stddev - (200.0f64 / 3.0).sqrt()).abs() < 1e-9);
+    assert!((report.clear_rate - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(report.hands[&Hand::Pair], 3.0);
+    assert_eq!(report.mean_micros, 100.0);
+    assert_eq!(
+        Report::of(&[result(10, false), result(20, false)]).median,
+        15.0
+    );
+    assert_eq!(
+        StrategyKind::parse("montecarlo:5"),
+        Some(StrategyKind::MonteCarlo(5))
+    );
+    assert_eq!(StrategyKind::parse("greedy:5"), None);
+}
@@ -0,0 +1,371 @@
+/// An exhaustive solver for a fully known deal.
+///
+/// Everything that matters about a position in the search is how many cards
+/// are left in each of the nine stacks and how many trashes are available;
+/// which cards are on top follows from the original deal. Positions are
+/// memoized on exactly that, so each is only searched once.
+use std::collections::HashSet;
+
+use crate::model::*;
+use crate::rules::Ruleset;
+
+pub struct Solution {
+    /// The best final score that can be reached.
+    pub score: u32,
+    /// A line of play that reaches it, with the stacks to select for each move.
+    pub moves: Vec<(Move, HashSet<Position>)>,
+    /// How many distinct positions were searched.
+    pub nodes: u64,
+}
+
+/// Stacks are numbered in `PILE_SIZES` order, and a selection of stacks is a
+/// bitmask over those numbers.
+type Heights = [usize; 9];
+
+/// Marks a position that hasn't been searched yet.
+const UNKNOWN: u16 = u16::MAX;
+
+/// The stacks in each row.
+const ROW_MASKS: [u16; 3] = [0b000_000_111, 0b000_111_000, 0b111_000_000];
+
+fn rows_in(mask: u16) -> usize {
+    ROW_MASKS.iter().filter(|&&row| mask & row != 0).count()
+}
+
+/// Every way of choosing `n` stacks from `mask`.
+fn choose(mask: u16, n: u32) -> impl Iterator<Item = u16> {
+    let first = if mask.count_ones() < n { 0 } else { mask };
+    let subsets = std::iter::successors(Some(first), move |&sub| match sub {
+        0 => None,
+        _ => Some((sub - 1) & mask),
+    });
+    subsets.filter(move |sub| sub.count_ones() == n)
+}
+
+/// Ranks in straight order, with the ace at both ends.
+fn rank_at(idx: usize) -> usize {
+    idx % 13
+}
+
+/// The top cards of the stacks, grouped so hands can be found without
+/// trying every selection.
+struct Tops {
+    by_rank: [u16; 13],
+    by_suit: [u16; 4],
+}
+
+impl Tops {
+    fn new(cards: &[Option<Card>]) -> Self {
+        let mut tops = Tops {
+            by_rank: [0; 13],
+            by_suit: [0; 4],
+        };
+        for (idx, card) in cards.iter().enumerate() {
+            if let Some(Card(r, s)) = card {
+                tops.by_rank[*r as usize] |= 1 << idx;
+                tops.by_suit[*s as usize] |= 1 << idx;
+            }
+        }
+        tops
+    }
+
+    fn is_flush(&self, mask: u16) -> bool {
+        self.by_suit.iter().any(|&suit| mask & suit == mask)
+    }
+
+    fn is_straight(&self, mask: u16) -> bool {
+        let len = mask.count_ones() as usize;
+        (0..=14 - len)
+            .any(|start| (start..start + len).all(|idx| self.by_rank[rank_at(idx)] & mask != 0))
+    }
+
+    /// Every hand among the top cards (ignoring which rows they're in).
+    fn hands(&self) -> Vec<(Hand, u16)> {
+        let mut hands = Vec::new();
+        for &group in self.by_rank.iter() {
+            for (n, h) in [
+                (2, Hand::Pair),
+                (3, Hand::ThreeOfAKind),
+                (4, Hand::FourOfAKind),
+            ] {
+                hands.extend(choose(group, n).map(|mask| (h, mask)));
+            }
+        }
+        for &(len, h) in &[(3, Hand::StraightThree), (5, Hand::StraightFive)] {
+            for start in 0..=14 - len {
+                if (start..start + len).any(|idx| self.by_rank[rank_at(idx)] == 0) {
+                    continue;
+                }
+                let mut masks = vec![0u16];
+                for idx in start..start + len {
+                    let group = self.by_rank[rank_at(idx)];
+                    masks = masks
+                        .iter()
+                        .flat_map(|&m| choose(group, 1).map(move |c| m | c))
+                        .collect();
+                }
+                for mask in masks {
+                    if len == 5 && self.is_flush(mask) {
+                        hands.push((Hand::StraightFlush, mask));
+                    } else {
+                        hands.push((h, mask));
+                    }
+                }
+            }
+        }
+        for &suit in self.by_suit.iter() {
+            for mask in choose(suit, 5) {
+                if !self.is_straight(mask) {
+                    hands.push((Hand::Flush, mask));
+                }
+            }
+        }
+        for (a, &three) in self.by_rank.iter().enumerate() {
+            for (b, &two) in self.by_rank.iter().enumerate() {
+                if a == b || three.count_ones() < 3 || two.count_ones() < 2 {
+                    continue;
+                }
+                for t in choose(three, 3) {
+                    for p in choose(two, 2) {
+                        hands.push((Hand::FullHouse, t | p));
+                    }
+                }
+            }
+        }
+        hands
+    }
+}
+
+struct Solver<'a> {
+    stacks: Vec<&'a CardStack>,
+    bonus_suit: Suit,
+    rules: &'a Ruleset,
+    /// The best score still to come from each position, indexed by stack
+    /// heights and trashes; see `index`.
+    memo: Vec<u16>,
+    nodes: u64,
+}
+
+fn trash_count(t: Trashes) -> usize {
+    match t {
+        Trashes::None => 0,
+        Trashes::One => 1,
+        Trashes::Two => 2,
+    }
+}
+
+impl<'a> Solver<'a> {
+    fn new(game: &'a Game) -> Self {
+        let stacks: Vec<_> = PILE_SIZES
+            .iter()
+            .map(|(pos, _)| game.spread.get_stack(*pos))
+            .collect();
+        let positions = stacks.iter().map(|s| s.len() + 1).product::<usize>() * 3;
+        let Card(_, bonus_suit) = game.bonus_card;
+        Solver {
+            stacks,
+            bonus_suit,
+            rules: game.rules(),
+            memo: vec![UNKNOWN; positions],
+            nodes: 0,
+        }
+    }
+
+    fn index(&self, heights: &Heights, trashes: usize) -> usize {
+        heights
+            .iter()
+            .zip(self.stacks.iter())
+            .fold(trashes, |k, (&h, stack)| k * (stack.len() + 1) + h)
+    }
+
+    fn tops(&self, heights: &Heights) -> Tops {
+        let cards: Vec<Option<Card>> = heights
+            .iter()
+            .zip(self.stacks.iter())
+            .map(|(&h, stack)| h.checked_sub(1).map(|top| stack[top]))
+            .collect();
+        Tops::new(&cards)
+    }
+
+    /// Every legal move from this position, with the points it scores and the
+    /// position it leads to.
+    fn moves(&self, heights: &Heights, trashes: usize) -> Vec<(Step, u32, Heights, usize)> {
+        let mut moves = Vec::new();
+        let tops = self.tops(heights);
+        for (h, mask) in tops.hands() {
+            if rows_in(mask) < self.rules.min_rows {
+                continue;
+            }
+            let doubled = mask & tops.by_suit[self.bonus_suit as usize] != 0;
+            let multiplier = if doubled {
+                self.rules.bonus_multiplier
+            } else {
+                1
+            };
+            let points = self.rules.points(h) * multiplier;
+            let mut next = *heights;
+            for (idx, height) in next.iter_mut().enumerate() {
+                if mask & (1 << idx) != 0 {
+                    *height -= 1;
+                }
+            }
+            let restored = (trashes + 1).min(trash_count(self.rules.max_trashes));
+            moves.push((Step::Hand(h, mask), points, next, restored));
+        }
+        if trashes > 0 {
+            for idx in 0..9 {
+                if heights[idx] > 0 {
+                    let mut next = *heights;
+                    next[idx] -= 1;
+                    moves.push((Step::Trash(idx), 0, next, trashes - 1));
+                }
+            }
+        }
+        moves
+    }
+
+    /// The best score still to come from this position, counting pile
+    /// bonuses but not hands already played.
+    fn value(&mut self, heights: &Heights, trashes: usize) -> u32 {
+        let k = self.index(heights, trashes);
+        if self.memo[k] != UNKNOWN {
+            return u32::from(self.memo[k]);
+        }
+        let mut best = pile_bonus(self.rules, heights);
+        for (_, points, next, next_trashes) in self.moves(heights, trashes) {
+            best = best.max(points + self.value(&next, next_trashes));
+        }
+        self.memo[k] = best as u16;
+        self.nodes += 1;
+        best
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Trash(usize),
+    Hand(Hand, u16),
+}
+
+fn pile_bonus(rules: &Ruleset, heights: &Heights) -> u32 {
+    (0..9)
+        .filter(|&idx| heights[idx] == 0)
+        .map(|idx| rules.row_bonus(PILE_SIZES[idx].0 .0))
+        .sum()
+}
+
+fn positions(mask: u16) -> HashSet<Position> {
+    (0..9)
+        .filter(|idx| mask & (1 << idx) != 0)
+        .map(|idx| PILE_SIZES[idx].0)
+        .collect()
+}
+
+/// Find the highest score that can be reached from `game`, and how.
+pub fn solve(game: &Game) -> Solution {
+    let mut solver = Solver::new(game);
+    let mut heights: Heights = [0; 9];
+    for (h, stack) in heights.iter_mut().zip(solver.stacks.iter()) {
+        *h = stack.len();
+    }
+    let mut trashes = trash_count(game.trashes);
+    let best = solver.value(&heights, trashes);
+
+    // Retrace the search, at each step taking a move that keeps the best
+    // score in reach.
+    let mut moves = Vec::new();
+    let mut to_come = best;
+    while to_come > pile_bonus(game.rules(), &heights) {
+        let (step, points, next, next_trashes) = solver
+            .moves(&heights, trashes)
+            .into_iter()
+            .find(|(_, points, next, next_trashes)| {
+                points + solver.value(next, *next_trashes) == to_come
+            })
+            .expect("The best line was searched");
+        moves.push(match step {
+            Step::Trash(idx) => (Move::Trash(PILE_SIZES[idx].0), positions(1 << idx)),
+            Step::Hand(h, mask) => (Move::PlayHand(h), positions(mask)),
+        });
+        to_come -= points;
+        heights = next;
+        trashes = next_trashes;
+    }
+    Solution {
+        score: game.hand_score() + best,
+        moves,
+        nodes: solver.nodes,
+    }
+}
+
+#[test]
+fn test_solve_empty() {
+    let mut g = Game::generate_with_seed(0);
+    g.spread = Spread::empty();
+    let s = solve(&g);
+    assert_eq!(s.score, 90);
+    assert!(s.moves.is_empty());
+}
+
+#[test]
+fn test_solve_prefers_clearing_top_row() {
+    // Trashing the top card uses a trash but earns the top row's bonus;
+    // the pair on the bottom is worth far less.
+    let mut g = Game::generate_with_seed(0);
+    g.spread = crate::notation::parse_spread("2h///5h///5s//").unwrap();
+    g.bonus_card = Card(Rank::King, Suit::Club);
+    let s = solve(&g);
+    // Pair (1) + everything cleared (90)
+    assert_eq!(s.score, 91);
+    assert_eq!(s.moves.len(), 2);
+}
+
+#[test]
+fn test_solution_replays() {
+    // The bottom three cards of six stacks
+    let mut g = Game::generate_with_seed(11);
+    for (pos @ Position(_, colid), _) in PILE_SIZES.iter() {
+        let kept = if *colid == ColumnId::Right { 0 } else { 3 };
+        g.spread.get_stack_mut(*pos).truncate(kept);
+    }
+    g.bonus_card = Card(Rank::King, Suit::Club);
+    let s = solve(&g);
+    assert!(s.nodes > 0);
+    for (mv, ps) in s.moves {
+        g.selected = ps;
+        assert_eq!(g.selected_move(), Some(mv));
+        crate::update::update(Msg::MakeMove, &mut g).unwrap();
+    }
+    assert_eq!(g.score(), s.score);
+}
+
+#[test]
+fn test_hands_match_classify_hand() {
+    let mut rng = DealRng::new(5);
+    for seed in 0..50 {
+        let g = Game::generate_with_seed(seed);
+        let cards: Vec<Option<Card>> = PILE_SIZES
+            .iter()
+            .map(|(pos, _)| {
+                let stack = g.spread.get_stack(*pos);
+                let depth = rng.below(stack.len() as u64 + 1) as usize;
+                stack.get(depth).copied()
+            })
+            .collect();
+        let tops = Tops::new(&cards);
+        let found = tops.hands();
+        for mask in 1u16..(1 << 9) {
+            let selected: Option<HashSet<Card>> = (0..9)
+                .filter(|idx| mask & (1 << idx) != 0)
+                .map(|idx| cards[idx])
+                .collect();
+            let expected = selected.and_then(|sel| classify_hand(&sel));
+            let actual: Vec<Hand> = found
+                .iter()
+                .filter(|(_, m)| *m == mask)
+                .map(|(h, _)| *h)
+                .collect();
+            assert_eq!(actual, expected.into_iter().collect::<Vec<_>>());
+        }
+    }
+}
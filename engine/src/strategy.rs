@@ -0,0 +1,257 @@
+/// Computer players.
+///
+/// A `Strategy` never gets the `Game` itself, only a `View` of it, which
+/// shows what a person at the table could see: the top cards, how tall each
+/// stack is, the bonus card and which cards are still somewhere in the spread.
+/// Unlike the solver, it has to cope with not knowing what's underneath.
+use std::collections::HashSet;
+
+use crate::model::*;
+use crate::rules::Ruleset;
+use crate::update::update;
+
+/// The visible parts of a game.
+pub struct View<'a> {
+    game: &'a Game,
+}
+
+impl<'a> View<'a> {
+    pub fn of(game: &'a Game) -> Self {
+        View { game }
+    }
+
+    /// The top card of a stack, if there is one.
+    pub fn top(&self, pos: Position) -> Option<Card> {
+        self.game.spread.get_stack(pos).last().copied()
+    }
+
+    pub fn size(&self, pos: Position) -> usize {
+        self.game.spread.get_stack(pos).len()
+    }
+
+    pub fn bonus_card(&self) -> Card {
+        self.game.bonus_card
+    }
+
+    pub fn trashes(&self) -> Trashes {
+        self.game.trashes
+    }
+
+    pub fn rules(&self) -> &Ruleset {
+        self.game.rules()
+    }
+
+    /// Every card still in the spread, whether it's showing or not.
+    pub fn remaining_cards(&self) -> HashSet<Card> {
+        self.game.remaining_cards()
+    }
+
+    /// Which moves can be made only depends on the top cards, so this is
+    /// fair game.
+    pub fn legal_moves(&self) -> Vec<(Move, HashSet<Position>)> {
+        self.game.legal_moves()
+    }
+
+    /// A game that looks just like this one, with the hidden cards dealt
+    /// at random.
+    pub fn sample(&self, rng: &mut DealRng) -> Game {
+        let mut hidden: Vec<Card> = self.remaining_cards().into_iter().collect();
+        hidden.sort();
+        hidden.retain(|c| !PILE_SIZES.iter().any(|(pos, _)| self.top(*pos) == Some(*c)));
+        for idx in (1..hidden.len()).rev() {
+            let other = rng.below(idx as u64 + 1) as usize;
+            hidden.swap(idx, other);
+        }
+
+        let mut g = Game::generate_with_seed(0)
+            .with_rules(self.rules().clone())
+            .expect("These rules already dealt the game being viewed");
+        g.spread = Spread::empty();
+        g.selected.clear();
+        g.trashes = self.trashes();
+        g.bonus_card = self.bonus_card();
+        for (pos, _) in PILE_SIZES.iter() {
+            if let Some(top) = self.top(*pos) {
+                let below = hidden.split_off(hidden.len() - (self.size(*pos) - 1));
+                let stack = g.spread.get_stack_mut(*pos);
+                stack.extend(below);
+                stack.push(top);
+            }
+        }
+        g
+    }
+}
+
+/// A way of picking moves.
+pub trait Strategy {
+    /// The next move to make and the stacks to select for it, or `None` if
+    /// there aren't any moves left.
+    fn choose(&mut self, view: &View) -> Option<(Move, HashSet<Position>)>;
+}
+
+/// Make a move chosen by a strategy.
+pub fn apply(game: &mut Game, (_, positions): (Move, HashSet<Position>)) -> Result<(), GameError> {
+    game.selected = positions;
+    update(Msg::MakeMove, game)
+}
+
+/// Play the hand worth the most right now; if there isn't one, trash from
+/// whichever stack is closest to being cleared.
+pub struct Greedy;
+
+/// Points for a move, counting any pile it clears.
+fn immediate_value(view: &View, mv: Move, positions: &HashSet<Position>) -> u32 {
+    let piles: u32 = positions
+        .iter()
+        .filter(|pos| view.size(**pos) == 1)
+        .map(|Position(rowid, _)| view.rules().row_bonus(*rowid))
+        .sum();
+    match mv {
+        Move::Trash(_) => piles,
+        Move::PlayHand(h) => {
+            let Card(_, bonus_suit) = view.bonus_card();
+            let doubled = positions
+                .iter()
+                .any(|pos| matches!(view.top(*pos), Some(Card(_, s)) if s == bonus_suit));
+            let multiplier = if doubled {
+                view.rules().bonus_multiplier
+            } else {
+                1
+            };
+            view.rules().points(h) * multiplier + piles
+        }
+    }
+}
+
+impl Strategy for Greedy {
+    fn choose(&mut self, view: &View) -> Option<(Move, HashSet<Position>)> {
+        let moves = view.legal_moves();
+        let best_hand = moves
+            .iter()
+            .filter(|(mv, _)| matches!(mv, Move::PlayHand(_)))
+            .max_by_key(|(mv, positions)| immediate_value(view, *mv, positions));
+        if let Some(m) = best_hand {
+            return Some(m.clone());
+        }
+        // Trashes are listed in `PILE_SIZES` order, so ties go to the top row.
+        moves.into_iter().min_by_key(|(mv, _)| match mv {
+            Move::Trash(pos) => view.size(*pos),
+            Move::PlayHand(_) => usize::MAX,
+        })
+    }
+}
+
+/// Try each move on a number of random deals consistent with what can be
+/// seen, finish each game greedily, and pick the move that scored best on
+/// average.
+pub struct MonteCarlo {
+    samples: usize,
+    rng: DealRng,
+}
+
+impl MonteCarlo {
+    pub fn new(samples: usize, seed: u64) -> Self {
+        MonteCarlo {
+            samples,
+            rng: DealRng::new(seed),
+        }
+    }
+}
+
+/// Make a move, then play the game out with `Greedy`, returning the points
+/// scored along the way.
+fn rollout(mut g: Game, first: (Move, HashSet<Position>)) -> u32 {
+    let start = g.score();
+    apply(&mut g, first).expect("Legal moves can always be made");
+    while let Some(m) = Greedy.choose(&View::of(&g)) {
+        apply(&mut g, m).expect("Greedy only chooses legal moves");
+    }
+    g.score() - start
+}
+
+impl Strategy for MonteCarlo {
+    fn choose(&mut self, view: &View) -> Option<(Move, HashSet<Position>)> {
+        let moves = view.legal_moves();
+        if moves.len() < 2 {
+            return moves.into_iter().next();
+        }
+        let mut totals = vec![0; moves.len()];
+        for _ in 0..self.samples {
+            // Every move is tried against the same deal, so they're compared
+            // fairly.
+            let seed = self.rng.next_u64();
+            for (total, m) in totals.iter_mut().zip(moves.iter()) {
+                let g = view.sample(&mut DealRng::new(seed));
+                *total += rollout(g, m.clone());
+            }
+        }
+        // Ties go to the first move listed
+        let best = (0..moves.len())
+            .rev()
+            .max_by_key(|&idx| totals[idx])
+            .unwrap();
+        moves.into_iter().nth(best)
+    }
+}
+
+#[test]
+fn test_samples_match_view() {
+    let mut g = Game::generate_with_seed(8);
+    g.spread.tl.pop();
+    g.spread.mc.clear();
+    let view = View::of(&g);
+    let mut rng = DealRng::new(1);
+    for _ in 0..20 {
+        let s = view.sample(&mut rng);
+        let sampled = View::of(&s);
+        for (pos, _) in PILE_SIZES.iter() {
+            assert_eq!(sampled.top(*pos), view.top(*pos));
+            assert_eq!(sampled.size(*pos), view.size(*pos));
+        }
+        assert_eq!(sampled.remaining_cards(), view.remaining_cards());
+        assert_eq!(s.bonus_card, g.bonus_card);
+    }
+}
+
+#[test]
+fn test_hidden_cards_dont_matter() {
+    // The same top cards with different cards underneath
+    let mut a = Game::generate_with_seed(0);
+    a.spread = crate::notation::parse_spread("9c2h///5h///0c5s//").unwrap();
+    let mut b = Game::generate_with_seed(0);
+    b.spread = crate::notation::parse_spread("0c2h///5h///9c5s//").unwrap();
+    let choice_a = MonteCarlo::new(10, 4).choose(&View::of(&a));
+    let choice_b = MonteCarlo::new(10, 4).choose(&View::of(&b));
+    assert!(choice_a.is_some());
+    assert_eq!(choice_a, choice_b);
+}
+
+#[test]
+fn test_monte_carlo_reaches_best_score() {
+    let mut g = Game::generate_with_seed(0);
+    g.spread = crate::notation::parse_spread("5h2h///2c/2d//5s//").unwrap();
+    let best = crate::solver::solve(&g).score;
+    let mut bot = MonteCarlo::new(5, 0);
+    while let Some(m) = bot.choose(&View::of(&g)) {
+        apply(&mut g, m).unwrap();
+    }
+    assert_eq!(g.score(), best);
+}
+
+#[test]
+fn test_strategies_finish_games() {
+    for strategy in &mut [
+        Box::new(Greedy) as Box<dyn Strategy>,
+        Box::new(MonteCarlo::new(2, 0)),
+    ] {
+        // A short game, so the test doesn't take long
+        let mut g = Game::generate_with_seed(21);
+        for (pos, _) in PILE_SIZES.iter() {
+            g.spread.get_stack_mut(*pos).truncate(2);
+        }
+        while let Some(m) = strategy.choose(&View::of(&g)) {
+            apply(&mut g, m).unwrap();
+        }
+        assert_ne!(g.status(), Status::InProgress);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::hint;
+use crate::model::*;
+
+/// Apply `msg` to `game`, noting it and what it did in the game's log. If
+/// it can't be applied, the game is left as it was and nothing is logged.
+pub fn update(msg: Msg, game: &mut Game) -> Result<(), GameError> {
+    let before = game.score();
+    let hint = game.hint.take();
+    let effect = match apply(msg, game, &hint) {
+        Ok(effect) => effect,
+        Err(e) => {
+            // Nothing happened, so the hint still stands
+            game.hint = hint;
+            return Err(e);
+        }
+    };
+    // A new or restarted deal starts a new log
+    if let Msg::NewGame | Msg::RestartDeal = msg {
+        return Ok(());
+    }
+    game.record(LogEntry {
+        msg,
+        effect,
+        score_delta: game.score() as i32 - before as i32,
+    });
+    Ok(())
+}
+
+/// `hint` is the one that was showing; it's cleared whatever `msg` is.
+fn apply(
+    msg: Msg,
+    game: &mut Game,
+    hint: &Option<(Move, HashSet<Position>)>,
+) -> Result<Option<Effect>, GameError> {
+    let finished = game.status() != Status::InProgress;
+    match msg {
+        Msg::MakeMove | Msg::Hint | Msg::ToggleStack(_) if finished => {
+            return Err(GameError::GameOver)
+        }
+        _ => (),
+    }
+    match msg {
+        Msg::MakeMove => {
+            let mv = game.check_move()?;
+            let positions = PILE_SIZES
+                .iter()
+                .map(|(pos, _)| *pos)
+                .filter(|pos| game.selected.contains(pos))
+                .collect();
+            game.checkpoint();
+            match mv {
+                Move::Trash(p) => game.trash(p)?,
+                Move::PlayHand(h) => game.play_hand(h)?,
+            }
+            Ok(Some(Effect::Played(mv, positions)))
+        }
+        Msg::NewGame => {
+            game.reset()?;
+            Ok(None)
+        }
+        Msg::RestartDeal => {
+            game.restart()?;
+            Ok(None)
+        }
+        Msg::Undo => {
+            game.undo()?;
+            Ok(Some(Effect::Undone))
+        }
+        Msg::Redo => {
+            game.redo()?;
+            Ok(Some(Effect::Redone))
+        }
+        Msg::Hint => {
+            match hint {
+                Some((_, positions)) => game.selected = positions.clone(),
+                None => game.hint = hint::suggest(game),
+            }
+            Ok(None)
+        }
+        Msg::ToggleStack(p) => {
+            if game.selected.contains(&p) {
+                game.selected.remove(&p);
+            } else if game.spread.get_stack(p).is_empty() {
+                return Err(GameError::EmptyStack(p));
+            } else {
+                game.selected.insert(p);
+            }
+            Ok(None)
+        }
+    }
+}
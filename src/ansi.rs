@@ -0,0 +1,267 @@
+/// An ANSI terminal backend, for playing in any terminal with 256-colour or
+/// truecolour support.
+///
+/// Drawing goes into an off-screen buffer of cells which is written out in
+/// one go on `refresh`, so the screen doesn't flicker.
+use std::io::{self, Write};
+
+use crossterm::event::{
+    self, KeyCode, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEventKind,
+};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+
+use crate::input::{Event, Events, Key};
+use crate::render::{self, Renderer, Rgb, BLACK};
+
+const DEFAULT_FG: Rgb = (255, 255, 255);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    fg: Rgb,
+    bg: Rgb,
+}
+
+const BLANK: Cell = Cell {
+    ch: ' ',
+    fg: DEFAULT_FG,
+    bg: BLACK,
+};
+
+pub struct Ansi {
+    cells: Vec<Cell>,
+    bg: Rgb,
+    truecolor: bool,
+    out: io::Stdout,
+}
+
+impl Ansi {
+    /// Take over the terminal. It's given back when this is dropped.
+    pub fn open() -> io::Result<Ansi> {
+        let truecolor = match std::env::var("COLORTERM") {
+            Ok(v) => v == "truecolor" || v == "24bit",
+            Err(_) => false,
+        };
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(
+            out,
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            event::EnableMouseCapture
+        )?;
+        Ok(Ansi {
+            cells: vec![BLANK; (render::WIDTH * render::HEIGHT) as usize],
+            bg: BLACK,
+            truecolor,
+            out,
+        })
+    }
+
+    fn color(&self, (r, g, b): Rgb) -> Color {
+        if self.truecolor {
+            Color::Rgb { r, g, b }
+        } else {
+            Color::AnsiValue(to_256(r, g, b))
+        }
+    }
+
+    fn put(&mut self, x: i32, y: i32, ch: char, fg: Rgb) {
+        if x < 0 || y < 0 || x >= render::WIDTH || y >= render::HEIGHT {
+            return;
+        }
+        let bg = self.bg;
+        self.cells[(y * render::WIDTH + x) as usize] = Cell { ch, fg, bg };
+    }
+
+    fn write_screen(&mut self) -> io::Result<()> {
+        let mut last: Option<(Rgb, Rgb)> = None;
+        for y in 0..render::HEIGHT {
+            queue!(self.out, cursor::MoveTo(0, y as u16))?;
+            for x in 0..render::WIDTH {
+                let cell = self.cells[(y * render::WIDTH + x) as usize];
+                if last != Some((cell.fg, cell.bg)) {
+                    let (fg, bg) = (self.color(cell.fg), self.color(cell.bg));
+                    queue!(self.out, SetForegroundColor(fg), SetBackgroundColor(bg))?;
+                    last = Some((cell.fg, cell.bg));
+                }
+                queue!(self.out, Print(cell.ch))?;
+            }
+        }
+        queue!(self.out, ResetColor)?;
+        self.out.flush()
+    }
+}
+
+impl Drop for Ansi {
+    fn drop(&mut self) {
+        let _ = execute!(
+            self.out,
+            event::DisableMouseCapture,
+            ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The nearest colour in the xterm 256-colour palette.
+fn to_256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        // Greyscale ramp, with the cube's black and white at either end
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            v => 232 + (v - 8) / 10,
+        };
+    }
+    let level = |c: u8| ((u16::from(c) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Read a `[color=R,G,B]` or `[/color]` tag at the start of `text`, returning
+/// the colour (`None` for a closing tag) and the tag's length.
+fn parse_tag(text: &str) -> Option<(Option<Rgb>, usize)> {
+    if text.starts_with("[/color]") {
+        return Some((None, "[/color]".len()));
+    }
+    let rest = text.strip_prefix("[color=")?;
+    let end = rest.find(']')?;
+    let parts: Vec<u8> = rest[..end]
+        .split(',')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [r, g, b] => Some((Some((*r, *g, *b)), "[color=".len() + end + 1)),
+        _ => None,
+    }
+}
+
+impl Renderer for Ansi {
+    fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = BLANK;
+        }
+    }
+
+    fn print_xy(&mut self, x: i32, y: i32, text: &str) {
+        let mut colors = vec![DEFAULT_FG];
+        let (mut cx, mut cy) = (x, y);
+        let mut idx = 0;
+        while idx < text.len() {
+            let rest = &text[idx..];
+            if rest.starts_with("[[") {
+                self.put(cx, cy, '[', *colors.last().unwrap());
+                cx += 1;
+                idx += 2;
+                continue;
+            }
+            if let Some((tag, len)) = parse_tag(rest) {
+                match tag {
+                    Some(c) => colors.push(c),
+                    None => {
+                        if colors.len() > 1 {
+                            colors.pop();
+                        }
+                    }
+                }
+                idx += len;
+                continue;
+            }
+            let ch = rest.chars().next().unwrap();
+            if ch == '\n' {
+                cx = x;
+                cy += 1;
+            } else {
+                self.put(cx, cy, ch, *colors.last().unwrap());
+                cx += 1;
+            }
+            idx += ch.len_utf8();
+        }
+    }
+
+    fn set_background(&mut self, bg: Rgb) {
+        self.bg = bg;
+    }
+
+    fn refresh(&mut self) {
+        // There's nowhere to report a broken terminal, so carry on and hope
+        // the next frame gets through.
+        let _ = self.write_screen();
+    }
+}
+
+/// Most terminals don't say whether a digit came from the number pad, but
+/// those that do set `KEYPAD`.
+fn translate_key(code: KeyCode, state: KeyEventState) -> Option<Key> {
+    let key = match code {
+        KeyCode::Char(c) if c.is_ascii_digit() && state.contains(KeyEventState::KEYPAD) => {
+            Key::Num(c as u8 - b'0')
+        }
+        KeyCode::Char(' ') => Key::Space,
+        KeyCode::Char(c) if c.is_ascii_alphanumeric() => Key::Char(c.to_ascii_lowercase()),
+        KeyCode::F(n) => Key::F(n),
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        _ => return None,
+    };
+    Some(key)
+}
+
+impl Events for Ansi {
+    fn wait_event(&mut self) -> Event {
+        loop {
+            let evt = match event::read() {
+                Ok(evt) => evt,
+                Err(_) => return Event::Close,
+            };
+            match evt {
+                event::Event::Key(k) if k.kind != KeyEventKind::Release => {
+                    if k.code == KeyCode::Char('c') && k.modifiers.contains(KeyModifiers::CONTROL) {
+                        return Event::Close;
+                    }
+                    if let Some(key) = translate_key(k.code, k.state) {
+                        return Event::Key(key);
+                    }
+                }
+                event::Event::Mouse(m) => {
+                    let (x, y) = (i32::from(m.column), i32::from(m.row));
+                    match m.kind {
+                        MouseEventKind::Down(MouseButton::Left) => return Event::Click(x, y),
+                        MouseEventKind::Moved => return Event::MouseMove(x, y),
+                        _ => continue,
+                    }
+                }
+                event::Event::Resize(_, _) => return Event::Redraw,
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_tag() {
+    assert_eq!(
+        parse_tag("[color=1,2,3]x"),
+        Some((Some((1, 2, 3)), "[color=1,2,3]".len()))
+    );
+    assert_eq!(parse_tag("[/color]x"), Some((None, 8)));
+    assert_eq!(parse_tag("[color=gray]"), None);
+    assert_eq!(parse_tag("[color=1,2]"), None);
+    assert_eq!(parse_tag("plain"), None);
+}
+
+#[test]
+fn test_to_256() {
+    assert_eq!(to_256(0, 0, 0), 16);
+    assert_eq!(to_256(255, 255, 255), 231);
+    assert_eq!(to_256(255, 0, 0), 196);
+    assert_eq!(to_256(128, 128, 128), 244);
+}
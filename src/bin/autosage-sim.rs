@@ -0,0 +1,201 @@
+/// Play a batch of games with a computer strategy and report how it did.
+///
+/// ```text
+/// autosage-sim --games 1000 --seed 1 --strategy montecarlo:20 --format csv
+/// ```
+use std::fs;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Instant;
+
+use autosage_engine::odds::HANDS;
+use autosage_engine::rules::{self, Ruleset};
+use autosage_engine::sim::{self, GameResult, Report, StrategyKind};
+
+#[derive(PartialEq)]
+enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+struct Args {
+    games: usize,
+    seed: u64,
+    strategy: StrategyKind,
+    rules: Ruleset,
+    jobs: usize,
+    format: Format,
+    out: Option<String>,
+}
+
+fn parse_args() -> Args {
+    let mut parsed = Args {
+        games: 100,
+        seed: 0,
+        strategy: StrategyKind::Greedy,
+        rules: Ruleset::standard(),
+        jobs: thread::available_parallelism().map_or(1, |n| n.get()),
+        format: Format::Text,
+        out: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let value = match value.as_deref() {
+            Some(v) => v,
+            None => usage_and_exit(&format!("{} needs a value", arg)),
+        };
+        match arg.as_str() {
+            "--games" => match value.parse() {
+                Ok(n) => parsed.games = n,
+                Err(_) => usage_and_exit("--games needs a whole number"),
+            },
+            "--seed" => match value.parse() {
+                Ok(s) => parsed.seed = s,
+                Err(_) => usage_and_exit("--seed needs a whole number"),
+            },
+            "--strategy" => match StrategyKind::parse(value) {
+                Some(s) => parsed.strategy = s,
+                None => usage_and_exit("--strategy needs greedy or montecarlo[:SAMPLES]"),
+            },
+            "--rules" => match Ruleset::preset(value) {
+                Some(r) => parsed.rules = r,
+                None => usage_and_exit(&format!(
+                    "--rules needs one of {}",
+                    rules::PRESETS.join(", ")
+                )),
+            },
+            "--jobs" => match value.parse() {
+                Ok(n) if n > 0 => parsed.jobs = n,
+                _ => usage_and_exit("--jobs needs a number above zero"),
+            },
+            "--format" => {
+                parsed.format = match value {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => usage_and_exit("--format needs text, csv or json"),
+                }
+            }
+            "--out" => parsed.out = Some(value.to_owned()),
+            _ => usage_and_exit(&format!("Unknown argument '{}'", arg)),
+        }
+    }
+    parsed
+}
+
+fn usage_and_exit(problem: &str) -> ! {
+    eprintln!("{}", problem);
+    eprintln!(
+        "usage: autosage-sim [--games N] [--seed BASE] [--strategy greedy|montecarlo[:SAMPLES]] \
+         [--rules {}] [--jobs N] [--format text|csv|json] [--out FILE]",
+        rules::PRESETS.join("|")
+    );
+    std::process::exit(2);
+}
+
+fn write_text(out: &mut dyn Write, args: &Args, report: &Report, secs: f64) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} games of {} under {} rules, from seed {}",
+        report.games, args.strategy, args.rules.name, args.seed
+    )?;
+    writeln!(
+        out,
+        "Score        mean {:.2}  median {:.1}  stddev {:.2}",
+        report.mean, report.median, report.stddev
+    )?;
+    writeln!(out, "Cleared      {:.1}%", report.clear_rate * 100.0)?;
+    writeln!(out, "Hands per game:")?;
+    for h in HANDS.iter() {
+        let count = report.hands.get(h).copied().unwrap_or(0.0);
+        writeln!(out, "  {: <20}{: >6.2}", h.to_string(), count)?;
+    }
+    writeln!(
+        out,
+        "Time         {:.2} ms per game, {:.2} s in all on {} threads",
+        report.mean_micros / 1000.0,
+        secs,
+        args.jobs
+    )
+}
+
+/// One row per game, with a column for each hand.
+fn write_csv(out: &mut dyn Write, results: &[GameResult]) -> io::Result<()> {
+    let hand_columns: Vec<String> = HANDS.iter().map(|h| format!("{:?}", h)).collect();
+    writeln!(
+        out,
+        "seed,score,cleared,moves,micros,{}",
+        hand_columns.join(",")
+    )?;
+    for r in results {
+        let counts: Vec<String> = HANDS
+            .iter()
+            .map(|h| r.hands.get(h).copied().unwrap_or(0).to_string())
+            .collect();
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            r.seed,
+            r.score,
+            r.cleared,
+            r.moves,
+            r.micros,
+            counts.join(",")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_json(
+    out: &mut dyn Write,
+    args: &Args,
+    report: &Report,
+    results: &[GameResult],
+) -> io::Result<()> {
+    let doc = serde_json::json!({
+        "strategy": args.strategy.to_string(),
+        "rules": args.rules.name,
+        "seed": args.seed,
+        "report": report,
+        "games": results,
+    });
+    serde_json::to_writer_pretty(&mut *out, &doc)?;
+    writeln!(out)
+}
+
+fn main() {
+    let args = parse_args();
+    let seeds = sim::seeds(args.seed, args.games);
+    let started = Instant::now();
+    let results = match sim::run(args.strategy, &args.rules, &seeds, args.jobs) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let secs = started.elapsed().as_secs_f64();
+    let report = Report::of(&results);
+
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => match fs::File::create(path) {
+            Ok(f) => Box::new(io::BufWriter::new(f)),
+            Err(e) => {
+                eprintln!("Couldn't create {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+    let written = match args.format {
+        Format::Text => write_text(&mut out, &args, &report, secs),
+        Format::Csv => write_csv(&mut out, &results),
+        Format::Json => write_json(&mut out, &args, &report, &results),
+    };
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,149 @@
+/// The BearLibTerminal backend: a window of its own.
+use bear_lib_terminal::terminal::config::{InputFilter, InputFilterGroup};
+use bear_lib_terminal::terminal::{self, KeyCode};
+use bear_lib_terminal::Color;
+
+use crate::input::{Event, Events, Key};
+use crate::render::{self, Renderer, Rgb};
+
+pub struct Blt;
+
+impl Blt {
+    /// Open the window. It's closed again when this is dropped.
+    pub fn open() -> Blt {
+        terminal::open("Auto-Sage", render::WIDTH as u32, render::HEIGHT as u32);
+        terminal::set(vec![
+            InputFilter::Group {
+                group: InputFilterGroup::Keyboard,
+                both: false,
+            },
+            InputFilter::Group {
+                group: InputFilterGroup::Mouse,
+                both: false,
+            },
+        ]);
+        Blt
+    }
+}
+
+impl Drop for Blt {
+    fn drop(&mut self) {
+        terminal::close();
+    }
+}
+
+impl Renderer for Blt {
+    fn clear(&mut self) {
+        terminal::clear(None);
+    }
+
+    fn print_xy(&mut self, x: i32, y: i32, text: &str) {
+        terminal::print_xy(x, y, text);
+    }
+
+    fn set_background(&mut self, (r, g, b): Rgb) {
+        terminal::set_background(Color::from_rgb(r, g, b));
+    }
+
+    fn refresh(&mut self) {
+        terminal::refresh();
+    }
+}
+
+fn translate_key(k: KeyCode) -> Option<Key> {
+    let key = match k {
+        KeyCode::A => Key::Char('a'),
+        KeyCode::B => Key::Char('b'),
+        KeyCode::C => Key::Char('c'),
+        KeyCode::D => Key::Char('d'),
+        KeyCode::E => Key::Char('e'),
+        KeyCode::F => Key::Char('f'),
+        KeyCode::G => Key::Char('g'),
+        KeyCode::H => Key::Char('h'),
+        KeyCode::I => Key::Char('i'),
+        KeyCode::J => Key::Char('j'),
+        KeyCode::K => Key::Char('k'),
+        KeyCode::L => Key::Char('l'),
+        KeyCode::M => Key::Char('m'),
+        KeyCode::N => Key::Char('n'),
+        KeyCode::O => Key::Char('o'),
+        KeyCode::P => Key::Char('p'),
+        KeyCode::Q => Key::Char('q'),
+        KeyCode::R => Key::Char('r'),
+        KeyCode::S => Key::Char('s'),
+        KeyCode::T => Key::Char('t'),
+        KeyCode::U => Key::Char('u'),
+        KeyCode::V => Key::Char('v'),
+        KeyCode::W => Key::Char('w'),
+        KeyCode::X => Key::Char('x'),
+        KeyCode::Y => Key::Char('y'),
+        KeyCode::Z => Key::Char('z'),
+        KeyCode::Row1 => Key::Char('1'),
+        KeyCode::Row2 => Key::Char('2'),
+        KeyCode::Row3 => Key::Char('3'),
+        KeyCode::Row4 => Key::Char('4'),
+        KeyCode::Row5 => Key::Char('5'),
+        KeyCode::Row6 => Key::Char('6'),
+        KeyCode::Row7 => Key::Char('7'),
+        KeyCode::Row8 => Key::Char('8'),
+        KeyCode::Row9 => Key::Char('9'),
+        KeyCode::Row0 => Key::Char('0'),
+        KeyCode::Num1 => Key::Num(1),
+        KeyCode::Num2 => Key::Num(2),
+        KeyCode::Num3 => Key::Num(3),
+        KeyCode::Num4 => Key::Num(4),
+        KeyCode::Num5 => Key::Num(5),
+        KeyCode::Num6 => Key::Num(6),
+        KeyCode::Num7 => Key::Num(7),
+        KeyCode::Num8 => Key::Num(8),
+        KeyCode::Num9 => Key::Num(9),
+        KeyCode::Num0 => Key::Num(0),
+        KeyCode::F1 => Key::F(1),
+        KeyCode::F2 => Key::F(2),
+        KeyCode::F3 => Key::F(3),
+        KeyCode::F4 => Key::F(4),
+        KeyCode::F5 => Key::F(5),
+        KeyCode::F6 => Key::F(6),
+        KeyCode::F7 => Key::F(7),
+        KeyCode::F8 => Key::F(8),
+        KeyCode::F9 => Key::F(9),
+        KeyCode::F10 => Key::F(10),
+        KeyCode::F11 => Key::F(11),
+        KeyCode::F12 => Key::F(12),
+        KeyCode::Space => Key::Space,
+        KeyCode::Enter | KeyCode::NumEnter => Key::Enter,
+        KeyCode::Escape => Key::Escape,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        _ => return None,
+    };
+    Some(key)
+}
+
+impl Events for Blt {
+    fn wait_event(&mut self) -> Event {
+        loop {
+            match terminal::wait_event() {
+                Some(terminal::Event::Close) => return Event::Close,
+                Some(terminal::Event::Resize { .. }) => return Event::Redraw,
+                Some(terminal::Event::MouseMove { x, y }) => return Event::MouseMove(x, y),
+                Some(terminal::Event::KeyPressed {
+                    key: KeyCode::MouseLeft,
+                    ..
+                }) => {
+                    let at = terminal::state::mouse::position();
+                    return Event::Click(at.x, at.y);
+                }
+                Some(terminal::Event::KeyPressed { key, .. }) => {
+                    if let Some(k) = translate_key(key) {
+                        return Event::Key(k);
+                    }
+                }
+                _ => continue, // Ignore other messages
+            }
+        }
+    }
+}
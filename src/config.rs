@@ -0,0 +1,121 @@
+/// Auto Sage's own settings, read from the `[autosage]` and
+/// `[autosage.keys]` sections of `autosage.ini`. BearLibTerminal reads the
+/// same file and ignores sections it doesn't know about.
+use std::fs;
+
+use crate::input::KeyMap;
+
+pub const CONFIG_PATH: &str = "autosage.ini";
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Config {
+    /// How many undos are allowed per game; `None` for no limit.
+    pub undo_limit: Option<u32>,
+    pub keys: KeyMap,
+}
+
+impl Config {
+    /// Load the config file, falling back to the defaults if it can't be
+    /// read or parsed. Also returns a warning for the player if it couldn't
+    /// be parsed.
+    pub fn load() -> (Config, Option<String>) {
+        let src = match fs::read_to_string(CONFIG_PATH) {
+            Ok(src) => src,
+            Err(_) => return (Config::default(), None),
+        };
+        match Config::parse(&src) {
+            Ok(cfg) => (cfg, None),
+            Err(msg) => {
+                let warning = format!("Ignoring {}: {}", CONFIG_PATH, msg);
+                (Config::default(), Some(warning))
+            }
+        }
+    }
+
+    pub fn parse(src: &str) -> Result<Config, String> {
+        let mut cfg = Config::default();
+        let mut bindings = Vec::new();
+        for (section, key, value) in parse_ini(src)? {
+            match section.as_str() {
+                "autosage" => match key.as_str() {
+                    "undo-limit" => cfg.undo_limit = parse_limit(&value)?,
+                    _ => return Err(format!("unknown setting '{}'", key)),
+                },
+                "autosage.keys" => bindings.push((key, value)),
+                _ => (),
+            }
+        }
+        cfg.keys = cfg
+            .keys
+            .with_bindings(&bindings)
+            .map_err(|e| format!("[autosage.keys]: {}", e))?;
+        Ok(cfg)
+    }
+}
+
+fn parse_limit(value: &str) -> Result<Option<u32>, String> {
+    match value {
+        "none" | "unlimited" => Ok(None),
+        _ => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("undo-limit should be a number or 'none', not '{}'", value)),
+    }
+}
+
+/// Split an ini file into `(section, key, value)` triples. Comments start
+/// with `;` or `#`.
+fn parse_ini(src: &str) -> Result<Vec<(String, String, String)>, String> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+    for (idx, line) in src.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_lowercase();
+            continue;
+        }
+        match line.find('=') {
+            Some(eq) => {
+                let key = line[..eq].trim().to_lowercase();
+                let value = line[eq + 1..].trim().to_owned();
+                entries.push((section.clone(), key, value));
+            }
+            None => return Err(format!("line {}: expected 'key = value'", idx + 1)),
+        }
+    }
+    Ok(entries)
+}
+
+#[test]
+fn test_parse_undo_limit() {
+    let src = "[BearLibTerminal]\nwindow.title = \"Auto Sage\"\n\n[autosage]\nundo-limit = 3\n";
+    assert_eq!(Config::parse(src).unwrap().undo_limit, Some(3));
+    let src = "[autosage]\nundo-limit = none\n";
+    assert_eq!(Config::parse(src).unwrap().undo_limit, None);
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(Config::parse("[autosage]\nundo-limit = lots\n").is_err());
+    assert!(Config::parse("[autosage]\ncolour = blue\n").is_err());
+    assert!(Config::parse("[autosage]\nundo-limit\n").is_err());
+}
+
+#[test]
+fn test_parse_keys() {
+    use crate::input::Key;
+    use crate::model::Msg;
+    let src = "[autosage.keys]\nundo = backspace\nhint = u\n";
+    let keys = Config::parse(src).unwrap().keys;
+    assert_eq!(keys.msg(Key::Backspace), Some(Msg::Undo));
+    assert_eq!(keys.msg(Key::Char('u')), Some(Msg::Hint));
+    assert_eq!(keys.msg(Key::Char('i')), None);
+    assert_eq!(
+        Config::parse("[autosage.keys]\nundo = y\n"),
+        Err("[autosage.keys]: Y is bound to both redo and undo".to_owned())
+    );
+}
@@ -0,0 +1,384 @@
+/// The daily deal: everyone who plays on the same (UTC) day gets the same
+/// spread, dealt from the date. Only the first attempt at each day's deal
+/// counts, and restarting it gives that attempt up. How it went is kept in
+/// `saves/daily.json` along with a short summary that can be pasted into
+/// chat.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::*;
+use crate::saves::{SaveError, SAVE_DIR};
+
+const DAILY_VERSION: u32 = 1;
+
+/// How many hands go on each line of a shared summary.
+const GLYPHS_PER_LINE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: u64) -> Date {
+        // Count from 0000-03-01 so leap days fall at the end of the year
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z % 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date {
+            year: year as u32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Date::from_days(secs / 86_400)
+    }
+
+    /// The seed of this day's deal, which reads as the date: 20261018.
+    pub fn seed(self) -> u64 {
+        u64::from(self.year * 10_000 + self.month * 100 + self.day)
+    }
+
+    fn from_seed(seed: u64) -> Option<Date> {
+        let date = Date {
+            year: (seed / 10_000) as u32,
+            month: (seed / 100 % 100) as u32,
+            day: (seed % 100) as u32,
+        };
+        let days_in_month = match date.month {
+            2 if date.year.is_multiple_of(4)
+                && (!date.year.is_multiple_of(100) || date.year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return None,
+        };
+        if (1970..10_000).contains(&date.year) && (1..=days_in_month).contains(&date.day) {
+            Some(date)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The deal for a day.
+pub fn game(date: Date) -> Game {
+    Game::generate_with_seed(date.seed())
+}
+
+/// Which day's deal `game` is, if it's the attempt that counts. Only games
+/// from `start` are, and only until they're restarted.
+pub fn date_of(game: &Game) -> Option<Date> {
+    if !game.is_daily() || game.is_custom_deal() || !game.rules().is_standard() {
+        return None;
+    }
+    Date::from_seed(game.seed())
+}
+
+/// How the first attempt at a daily deal ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub score: u32,
+    pub cards_left: usize,
+    /// In the order they were played.
+    pub hands: Vec<Hand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyRecord {
+    pub date: Date,
+    /// How many times the deal has been started.
+    pub attempts: u32,
+    /// `None` until the first attempt is finished.
+    pub result: Option<DailyResult>,
+}
+
+/// Whether a daily deal that's being started will count.
+#[derive(Debug, PartialEq)]
+pub enum Attempt {
+    First,
+    /// The deal has been tried before; this is how that went.
+    Repeat(DailyRecord),
+}
+
+/// Note that `date`'s deal is being started.
+fn begin(days: &mut Vec<DailyRecord>, date: Date) -> Attempt {
+    match days.iter_mut().find(|r| r.date == date) {
+        Some(record) => {
+            let before = record.clone();
+            record.attempts += 1;
+            Attempt::Repeat(before)
+        }
+        None => {
+            days.push(DailyRecord {
+                date,
+                attempts: 1,
+                result: None,
+            });
+            Attempt::First
+        }
+    }
+}
+
+/// Record how a finished game went, if it's the first attempt at a daily
+/// deal.
+fn complete(days: &mut [DailyRecord], game: &Game) -> Option<(Date, DailyResult)> {
+    let date = date_of(game)?;
+    let record = days
+        .iter_mut()
+        .find(|r| r.date == date && r.attempts == 1 && r.result.is_none())?;
+    let hands = game
+        .moves_made()
+        .into_iter()
+        .filter_map(|mv| match mv {
+            Move::PlayHand(h) => Some(h),
+            Move::Trash(_) => None,
+        })
+        .collect();
+    let result = DailyResult {
+        score: game.score(),
+        cards_left: game.cards_left(),
+        hands,
+    };
+    record.result = Some(result.clone());
+    Some((date, result))
+}
+
+fn glyph(h: Hand) -> char {
+    match h {
+        Hand::Pair => '⬜',
+        Hand::StraightThree => '🟦',
+        Hand::ThreeOfAKind => '🟩',
+        Hand::StraightFive => '🟨',
+        Hand::FullHouse => '🟧',
+        Hand::Flush => '🟥',
+        Hand::FourOfAKind => '🟪',
+        Hand::StraightFlush => '⭐',
+    }
+}
+
+/// A few lines to paste into chat: the day, the score and each hand played.
+pub fn share(date: Date, result: &DailyResult) -> String {
+    let outcome = match result.cards_left {
+        0 => "every card cleared".to_owned(),
+        1 => "1 card left".to_owned(),
+        n => format!("{} cards left", n),
+    };
+    let mut lines = vec![
+        format!("Auto Sage daily {}", date),
+        format!("{} points, {}", result.score, outcome),
+    ];
+    for chunk in result.hands.chunks(GLYPHS_PER_LINE) {
+        lines.push(chunk.iter().map(|h| glyph(*h)).collect());
+    }
+    lines.join("\n")
+}
+
+#[derive(Serialize, Deserialize)]
+struct DailyFile {
+    version: u32,
+    days: Vec<DailyRecord>,
+}
+
+fn path() -> PathBuf {
+    [SAVE_DIR, "daily.json"].iter().collect()
+}
+
+fn load() -> Result<Vec<DailyRecord>, SaveError> {
+    let path = path();
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(SaveError::Io(path, e)),
+    };
+    let file: DailyFile =
+        serde_json::from_str(&src).map_err(|e| SaveError::Corrupt(path.clone(), e.to_string()))?;
+    if file.version != DAILY_VERSION {
+        return Err(SaveError::Corrupt(
+            path,
+            format!("unknown daily version {}", file.version),
+        ));
+    }
+    Ok(file.days)
+}
+
+fn store(days: Vec<DailyRecord>) -> Result<(), SaveError> {
+    let path = path();
+    let file = DailyFile {
+        version: DAILY_VERSION,
+        days,
+    };
+    let src = serde_json::to_string(&file).expect("Daily records can always be serialized");
+    fs::create_dir_all(SAVE_DIR).map_err(|e| SaveError::Io(path.clone(), e))?;
+    fs::write(&path, src).map_err(|e| SaveError::Io(path, e))
+}
+
+/// Start `date`'s deal, noting the attempt. Also returns a message for the
+/// player saying whether it counts.
+pub fn start(date: Date) -> Result<(Game, String), SaveError> {
+    let mut days = load()?;
+    let attempt = begin(&mut days, date);
+    store(days)?;
+    let first = attempt == Attempt::First;
+    let msg = match attempt {
+        Attempt::First => format!(
+            "The daily deal for {}. Only this attempt counts, so don't restart!",
+            date
+        ),
+        Attempt::Repeat(DailyRecord {
+            result: Some(result),
+            ..
+        }) => format!(
+            "You scored {} on this deal already, so this game won't count.",
+            result.score
+        ),
+        Attempt::Repeat(_) => {
+            "You've started this deal before, so this game won't count.".to_owned()
+        }
+    };
+    let mut g = game(date);
+    if first {
+        g.mark_daily();
+    }
+    Ok((g, msg))
+}
+
+/// Call when a game is over. If it was the first attempt at a daily deal,
+/// records the result and writes a summary to share next to the saves, then
+/// returns the summary and where it went.
+pub fn finish(game: &Game) -> Result<Option<(String, PathBuf)>, SaveError> {
+    if date_of(game).is_none() {
+        return Ok(None);
+    }
+    let mut days = load()?;
+    let (date, result) = match complete(&mut days, game) {
+        Some(done) => done,
+        None => return Ok(None),
+    };
+    store(days)?;
+    let text = share(date, &result);
+    let path: PathBuf = [SAVE_DIR, &format!("daily-{}.txt", date)].iter().collect();
+    fs::write(&path, &text).map_err(|e| SaveError::Io(path.clone(), e))?;
+    Ok(Some((text, path)))
+}
+
+#[test]
+fn test_dates() {
+    let cases = [
+        (0, "1970-01-01"),
+        (11_016, "2000-02-29"),
+        (20_088, "2024-12-31"),
+        (20_744, "2026-10-18"),
+    ];
+    for (days, expected) in cases.iter() {
+        let date = Date::from_days(*days);
+        assert_eq!(date.to_string(), *expected);
+        assert_eq!(Date::from_seed(date.seed()), Some(date));
+    }
+    assert_eq!(Date::from_days(20_744).seed(), 20_261_018);
+    assert_eq!(Date::from_seed(20_260_230), None);
+    assert_eq!(Date::from_seed(1234), None);
+}
+
+#[test]
+fn test_first_attempt_counts() {
+    use crate::update::update;
+    use autosage_engine::notation;
+    let date = Date::from_days(20_744);
+    let mut days = Vec::new();
+    assert_eq!(begin(&mut days, date), Attempt::First);
+
+    let mut g = game(date);
+    g.mark_daily();
+    g.spread = notation::parse_spread("5c//////5h//").unwrap();
+    g.selected = [
+        Position(RowId::Top, ColumnId::Left),
+        Position(RowId::Bottom, ColumnId::Left),
+    ]
+    .iter()
+    .copied()
+    .collect();
+    update(Msg::MakeMove, &mut g).unwrap();
+    let (done, result) = complete(&mut days, &g).unwrap();
+    assert_eq!(done, date);
+    assert_eq!(result.hands, vec![Hand::Pair]);
+    assert_eq!(result.cards_left, 0);
+    assert_eq!(
+        share(date, &result),
+        format!(
+            "Auto Sage daily 2026-10-18\n{} points, every card cleared\n⬜",
+            g.score()
+        )
+    );
+    // Finishing again, or on a second attempt, changes nothing
+    assert_eq!(complete(&mut days, &g), None);
+    assert!(matches!(
+        begin(&mut days, date),
+        Attempt::Repeat(DailyRecord {
+            attempts: 1,
+            result: Some(_),
+            ..
+        })
+    ));
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].attempts, 2);
+
+    let other_day = Date::from_days(20_745);
+    begin(&mut days, other_day);
+    begin(&mut days, other_day);
+    let mut again = game(other_day);
+    again.mark_daily();
+    again.spread = g.spread.clone();
+    assert_eq!(complete(&mut days, &again), None);
+}
+
+#[test]
+fn test_only_started_dailies_count() {
+    use crate::update::update;
+    let date = Date::from_days(20_744);
+    let mut days = Vec::new();
+    begin(&mut days, date);
+
+    // The same seed, dealt some other way
+    let g = Game::generate_with_seed(date.seed());
+    assert_eq!(date_of(&g), None);
+
+    // Restarting forfeits the attempt
+    let mut g = game(date);
+    g.mark_daily();
+    assert_eq!(date_of(&g), Some(date));
+    update(Msg::RestartDeal, &mut g).unwrap();
+    assert_eq!(date_of(&g), None);
+    g.spread = Spread::empty();
+    assert_eq!(complete(&mut days, &g), None);
+    assert_eq!(days[0].result, None);
+}
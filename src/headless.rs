@@ -0,0 +1,600 @@
+/// A plain stdin/stdout frontend that doesn't need BearLibTerminal.
+///
+/// Each line of input is a command; the board is printed after every one.
+/// Because it reads and writes ordinary streams, it can be scripted.
+use std::io::{self, BufRead, Write};
+
+use crate::daily;
+use crate::hint;
+use crate::model::*;
+use crate::odds::{self, Chance};
+use crate::render;
+use crate::replay::{self, Replay};
+use crate::saves::{self, Slot};
+use crate::stats::{self, Summary, Tracker};
+use crate::strategy::{self, MonteCarlo, Strategy, View};
+use crate::update::update;
+
+const HELP: &str = "\
+Commands:
+  tl mc br     toggle stacks (t/m/b for the row, l/c/r for the column)
+  play         play the selected hand or trash the selected card
+  trash tl     trash the top card of one stack
+  clear        clear the selection
+  undo, redo   take back or re-apply a move
+  new          deal a new game
+  restart      start this deal over
+  cards        list the cards still in the spread
+  graveyard    list the cards taken off the spread, and how
+  stats        show statistics for finished games
+  deal         show this deal in notation, for --deal
+  odds         show the chance of each hand still being playable
+  under tl     show what could be under the top card of a stack
+  hint         suggest a move (again to select it)
+  bot          let the computer make a move
+  save N       save to slot N (1-5)
+  load N       load from slot N
+  export FILE  write the moves so far to a replay file
+  help         show this message
+  quit         leave";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Toggle(Vec<Position>),
+    Play,
+    Trash(Position),
+    Clear,
+    Send(Msg),
+    Cards,
+    Graveyard,
+    Stats,
+    Deal,
+    Odds,
+    Under(Position),
+    Bot,
+    Save(Slot),
+    Load(Slot),
+    Export(String),
+    Help,
+    Quit,
+}
+
+pub fn parse_position(src: &str) -> Option<Position> {
+    let mut chars = src.chars();
+    let r = match chars.next()? {
+        't' => RowId::Top,
+        'm' => RowId::Middle,
+        'b' => RowId::Bottom,
+        _ => return None,
+    };
+    let c = match chars.next()? {
+        'l' => ColumnId::Left,
+        'c' => ColumnId::Center,
+        'r' => ColumnId::Right,
+        _ => return None,
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(Position(r, c))
+}
+
+fn parse_slot(src: &str) -> Result<Slot, String> {
+    match src.parse::<usize>() {
+        Ok(n) if (1..=saves::SLOTS).contains(&n) => Ok(Slot::Numbered(n)),
+        _ => Err(format!("Slots are numbered 1 to {}", saves::SLOTS)),
+    }
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    // File names keep their case
+    let original: Vec<&str> = line.split_whitespace().collect();
+    let line = line.trim().to_lowercase();
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["play"] => Ok(Command::Play),
+        ["trash", p] => parse_position(p)
+            .map(Command::Trash)
+            .ok_or_else(|| format!("'{}' isn't a stack", p)),
+        ["clear"] => Ok(Command::Clear),
+        ["undo"] => Ok(Command::Send(Msg::Undo)),
+        ["redo"] => Ok(Command::Send(Msg::Redo)),
+        ["new"] => Ok(Command::Send(Msg::NewGame)),
+        ["restart"] => Ok(Command::Send(Msg::RestartDeal)),
+        ["hint"] => Ok(Command::Send(Msg::Hint)),
+        ["cards"] => Ok(Command::Cards),
+        ["graveyard"] => Ok(Command::Graveyard),
+        ["deal"] => Ok(Command::Deal),
+        ["stats"] => Ok(Command::Stats),
+        ["odds"] => Ok(Command::Odds),
+        ["under", p] => parse_position(p)
+            .map(Command::Under)
+            .ok_or_else(|| format!("'{}' isn't a stack", p)),
+        ["bot"] => Ok(Command::Bot),
+        ["save", n] => parse_slot(n).map(Command::Save),
+        ["load", n] => parse_slot(n).map(Command::Load),
+        ["export", _] => Ok(Command::Export(original[1].to_owned())),
+        ["help"] => Ok(Command::Help),
+        ["quit"] | ["exit"] => Ok(Command::Quit),
+        [] => Err("Type 'help' for a list of commands".to_owned()),
+        _ => {
+            let mut positions = Vec::new();
+            for w in words.iter() {
+                match parse_position(w) {
+                    Some(p) => positions.push(p),
+                    None => return Err(format!("Unknown command '{}'", w)),
+                }
+            }
+            Ok(Command::Toggle(positions))
+        }
+    }
+}
+
+/// How many deals the bot imagines for each move it considers.
+const BOT_SAMPLES: usize = 20;
+
+fn clear_selection(game: &mut Game) -> Result<(), GameError> {
+    let selected: Vec<Position> = game.selected.iter().copied().collect();
+    for p in selected {
+        update(Msg::ToggleStack(p), game)?;
+    }
+    Ok(())
+}
+
+/// Say why a change to the game couldn't be made, if it couldn't.
+fn report<W: Write>(result: Result<(), GameError>, out: &mut W) -> io::Result<()> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => writeln!(out, "{}", e),
+    }
+}
+
+const ROWS: [(RowId, char); 3] = [
+    (RowId::Top, 't'),
+    (RowId::Middle, 'm'),
+    (RowId::Bottom, 'b'),
+];
+const COLUMNS: [(ColumnId, char); 3] = [
+    (ColumnId::Left, 'l'),
+    (ColumnId::Center, 'c'),
+    (ColumnId::Right, 'r'),
+];
+
+fn print_game<W: Write>(g: &Game, out: &mut W) -> io::Result<()> {
+    for (rowid, r) in ROWS.iter() {
+        let mut line = String::new();
+        for (colid, c) in COLUMNS.iter() {
+            let pos = Position(*rowid, *colid);
+            let stack = g.spread.get_stack(pos);
+            let hinted = match &g.hint {
+                Some((_, positions)) => positions.contains(&pos),
+                None => false,
+            };
+            let mark = if g.selected.contains(&pos) {
+                '*'
+            } else if hinted {
+                '?'
+            } else {
+                ' '
+            };
+            let cell = match stack.last() {
+                Some(card) => format!("{}{}{}{: >2}|{}", r, c, mark, stack.len(), card),
+                None => format!("{}{}{} 0|{: >+3}", r, c, mark, g.rules().row_bonus(*rowid)),
+            };
+            line.push_str(&format!("{: <12}", cell));
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    let trashes = match g.trashes {
+        Trashes::None => 0,
+        Trashes::One => 1,
+        Trashes::Two => 2,
+    };
+    let seed = if g.is_custom_deal() {
+        "custom".to_owned()
+    } else {
+        g.seed().to_string()
+    };
+    writeln!(
+        out,
+        "Score {}  Trashes {}  Bonus {}  Seed {}",
+        g.score(),
+        trashes,
+        g.bonus_card,
+        seed
+    )?;
+    if !g.rules().is_standard() {
+        writeln!(out, "Rules {}", g.rules().name)?;
+    }
+    match g.status() {
+        Status::InProgress => {
+            if let Some(preview) = g.preview_move() {
+                match render::describe_move(&preview) {
+                    (headline, Some(breakdown)) => {
+                        writeln!(out, "Move: {}: {}", headline, breakdown)?
+                    }
+                    (headline, None) => writeln!(out, "Move: {}", headline)?,
+                }
+            }
+            if let Some(reason) = g.explain_selection() {
+                writeln!(out, "No move: {}", reason)?;
+            }
+            if let Some((mv, positions)) = &g.hint {
+                writeln!(out, "Hint: {}", hint::explain(g, *mv, positions))?;
+            }
+        }
+        status => {
+            let undos = match g.undo_limit() {
+                Some(limit) => format!("{}/{}", g.undos_used(), limit),
+                None => format!("{}", g.undos_used()),
+            };
+            let headline = match status {
+                Status::Won => "All piles cleared!",
+                _ => "No moves left.",
+            };
+            writeln!(
+                out,
+                "{} Hands {} + piles {} = {} points, {} cards left, {} undos used",
+                headline,
+                g.hand_score(),
+                g.pile_bonus(),
+                g.score(),
+                g.cards_left(),
+                undos
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn print_remaining<W: Write>(g: &Game, out: &mut W) -> io::Result<()> {
+    let remaining = g.remaining_cards();
+    for &rank in RANKS.iter() {
+        let cards: Vec<String> = SUITS
+            .iter()
+            .map(|&suit| {
+                let card = Card(rank, suit);
+                if remaining.contains(&card) {
+                    format!("{}", card)
+                } else {
+                    "   ".to_owned()
+                }
+            })
+            .collect();
+        writeln!(out, "{}", cards.join(" ").trim_end())?;
+    }
+    Ok(())
+}
+
+fn print_graveyard<W: Write>(g: &Game, out: &mut W) -> io::Result<()> {
+    if g.discards().is_empty() {
+        return writeln!(out, "No cards have been taken off the spread.");
+    }
+    for d in g.discards() {
+        let cause = match d.cause {
+            Move::Trash(pos) => format!("trashed from {}", pos),
+            Move::PlayHand(h) => h.to_string(),
+        };
+        writeln!(out, "{: >3}. {} {}", d.turn, d.card, cause)?;
+    }
+    Ok(())
+}
+
+fn percent(c: Chance) -> String {
+    if c.dead {
+        "--".to_owned()
+    } else {
+        format!("{}%", (c.p * 100.0).round() as u32)
+    }
+}
+
+fn print_odds<W: Write>(g: &Game, out: &mut W) -> io::Result<()> {
+    let odds = odds::compute(g);
+    for (h, c) in odds::HANDS.iter().zip(odds.hands.iter()) {
+        writeln!(out, "{: <20}{: >5}", h.to_string(), percent(*c))?;
+    }
+    let flushes: Vec<String> = SUITS
+        .iter()
+        .zip(odds.flushes.iter())
+        .map(|(s, c)| format!("{} {}", s, percent(*c)))
+        .collect();
+    writeln!(out, "Flush by suit: {}", flushes.join("  "))?;
+    writeln!(out, "      2x   3x   4x")?;
+    for (&rank, kinds) in RANKS.iter().zip(odds.of_a_kind.iter()) {
+        let kinds: Vec<String> = kinds
+            .iter()
+            .map(|c| format!("{: >5}", percent(*c)))
+            .collect();
+        writeln!(out, "{}{}", rank, kinds.join(""))?;
+    }
+    Ok(())
+}
+
+fn print_buried<W: Write>(g: &Game, pos: Position, out: &mut W) -> io::Result<()> {
+    let b = odds::buried(&View::of(g), pos);
+    writeln!(
+        out,
+        "{} cards under {}; each of the {} unseen cards has a {:.0}% chance of being there",
+        b.hidden,
+        pos,
+        b.unseen.len(),
+        b.each * 100.0
+    )?;
+    for (&rank, p) in RANKS.iter().zip(b.by_rank.iter()) {
+        let cards: Vec<String> = SUITS
+            .iter()
+            .map(|&suit| {
+                let card = Card(rank, suit);
+                if b.unseen.contains(&card) {
+                    format!("{}", card)
+                } else {
+                    "   ".to_owned()
+                }
+            })
+            .collect();
+        writeln!(
+            out,
+            "{}  {: >3.0}% at least one",
+            cards.join(" "),
+            p * 100.0
+        )?;
+    }
+    let suits: Vec<String> = SUITS
+        .iter()
+        .zip(b.by_suit.iter())
+        .map(|(s, e)| format!("{} {:.1}", s, e))
+        .collect();
+    writeln!(out, "Expected by suit: {}", suits.join("  "))
+}
+
+fn print_stats<W: Write>(s: &Summary, out: &mut W) -> io::Result<()> {
+    if s.games == 0 {
+        return writeln!(out, "No games finished yet.");
+    }
+    let secs = s.mean_duration.as_secs();
+    writeln!(
+        out,
+        "{} games, {:.0}% won, average score {:.1}, best {}",
+        s.games,
+        s.win_rate() * 100.0,
+        s.mean_score,
+        s.best_score
+    )?;
+    writeln!(
+        out,
+        "Win streak {} (best {}), average time {}:{:02}, {:.1} trashes used",
+        s.current_streak,
+        s.best_streak,
+        secs / 60,
+        secs % 60,
+        s.mean_trashes
+    )?;
+    let [top, middle, bottom] = s.mean_piles;
+    writeln!(
+        out,
+        "Piles cleared per game: top {:.1}, middle {:.1}, bottom {:.1}",
+        top, middle, bottom
+    )?;
+    writeln!(out, "Hands per game:")?;
+    for h in odds::HANDS.iter() {
+        let n = s.hands.get(h).copied().unwrap_or(0.0);
+        writeln!(out, "  {: <20}{: >5.1}", h.to_string(), n)?;
+    }
+    let most = s
+        .histogram
+        .iter()
+        .map(|(_, n)| *n)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    for (low, n) in s.histogram.iter() {
+        let label = format!("{}-{}", low, low + s.bucket_width - 1);
+        writeln!(
+            out,
+            "{: >7} {} {}",
+            label,
+            "#".repeat((n * 30).div_ceil(most)),
+            n
+        )?;
+    }
+    Ok(())
+}
+
+/// Play `game` with commands read from `input` until it runs out or the
+/// player quits.
+pub fn run<R: BufRead, W: Write>(game: &mut Game, input: R, mut out: W) -> io::Result<()> {
+    let mut bot = MonteCarlo::new(BOT_SAMPLES, game.seed());
+    let mut tracker = Tracker::new(game);
+    print_game(game, &mut out)?;
+    write!(out, "> ")?;
+    out.flush()?;
+    for line in input.lines() {
+        match parse_command(&line?) {
+            Ok(Command::Quit) => break,
+            Ok(Command::Help) => {
+                writeln!(out, "{}\n", HELP)?;
+                for line in render::describe_rules(game.rules()) {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            Ok(Command::Toggle(positions)) => {
+                for p in positions {
+                    report(update(Msg::ToggleStack(p), game), &mut out)?;
+                }
+            }
+            Ok(Command::Play) => report(update(Msg::MakeMove, game), &mut out)?,
+            Ok(Command::Trash(p)) => {
+                let trashed = clear_selection(game)
+                    .and_then(|_| update(Msg::ToggleStack(p), game))
+                    .and_then(|_| update(Msg::MakeMove, game));
+                if let Err(e) = trashed {
+                    writeln!(out, "Can't trash that: {}", e)?;
+                }
+            }
+            Ok(Command::Clear) => report(clear_selection(game), &mut out)?,
+            Ok(Command::Send(msg)) => {
+                report(update(msg, game), &mut out)?;
+                if let Msg::NewGame | Msg::RestartDeal = msg {
+                    tracker = Tracker::new(game);
+                }
+            }
+            Ok(Command::Stats) => match stats::load() {
+                Ok(games) => print_stats(&Summary::of(&games), &mut out)?,
+                Err(e) => writeln!(out, "{}", e)?,
+            },
+            Ok(Command::Cards) => print_remaining(game, &mut out)?,
+            Ok(Command::Graveyard) => print_graveyard(game, &mut out)?,
+            Ok(Command::Deal) => writeln!(out, "{}", game.to_notation())?,
+            Ok(Command::Odds) => print_odds(game, &mut out)?,
+            Ok(Command::Under(p)) => print_buried(game, p, &mut out)?,
+            Ok(Command::Bot) => match bot.choose(&View::of(game)) {
+                Some(m) => {
+                    let stacks: Vec<String> = PILE_SIZES
+                        .iter()
+                        .filter(|(pos, _)| m.1.contains(pos))
+                        .map(|(pos, _)| pos.to_string())
+                        .collect();
+                    match m.0 {
+                        Move::Trash(_) => writeln!(out, "Bot: Trash {}", stacks.join(" "))?,
+                        Move::PlayHand(h) => writeln!(out, "Bot: {} {}", h, stacks.join(" "))?,
+                    }
+                    report(strategy::apply(game, m), &mut out)?;
+                }
+                None => writeln!(out, "No moves left.")?,
+            },
+            Ok(Command::Save(slot)) => match saves::save(game, slot) {
+                Ok(()) => writeln!(out, "Game saved.")?,
+                Err(e) => writeln!(out, "{}", e)?,
+            },
+            Ok(Command::Load(slot)) => match saves::load(slot) {
+                Ok(Some(loaded)) => {
+                    let limit = game.undo_limit();
+                    *game = loaded;
+                    game.set_undo_limit(limit);
+                    tracker = Tracker::new(game);
+                }
+                Ok(None) => writeln!(out, "That slot is empty.")?,
+                Err(e) => writeln!(out, "{}", e)?,
+            },
+            Ok(Command::Export(file)) => match replay::save(game, file.as_ref()) {
+                Ok(()) => writeln!(out, "Replay saved to {}.", file)?,
+                Err(e) => writeln!(out, "Couldn't save the replay: {}", e)?,
+            },
+            Err(e) => writeln!(out, "{}", e)?,
+        }
+        if let Some(record) = tracker.observe(game) {
+            if let Err(e) = stats::record(record) {
+                writeln!(out, "{}", e)?;
+            }
+            match daily::finish(game) {
+                Ok(Some((text, path))) => {
+                    writeln!(out, "{}\n(Saved to {} for sharing.)", text, path.display())?
+                }
+                Ok(None) => (),
+                Err(e) => writeln!(out, "{}", e)?,
+            }
+        }
+        print_game(game, &mut out)?;
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Step through a replay with `next` (or just Enter), `prev`, `goto N` and
+/// `quit`, printing the board after each step.
+pub fn run_replay<R: BufRead, W: Write>(replay: &Replay, input: R, mut out: W) -> io::Result<()> {
+    let divergence = replay.check();
+    let last = match &divergence {
+        Some(d) => d.step - 1,
+        None => replay.steps.len(),
+    };
+    let mut step = 0;
+    let mut lines = input.lines();
+    loop {
+        let (g, _) = replay.game_at(step);
+        print_game(&g, &mut out)?;
+        match step {
+            0 => writeln!(out, "Start of {} moves", replay.steps.len())?,
+            _ => writeln!(
+                out,
+                "Move {} of {}: {}",
+                step,
+                replay.steps.len(),
+                replay.steps[step - 1]
+            )?,
+        }
+        if let (Some(d), true) = (&divergence, step == last) {
+            writeln!(out, "{}", d)?;
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["next"] | ["n"] if step < last => step += 1,
+            [] | ["next"] | ["n"] => writeln!(out, "That's the last move.")?,
+            ["prev"] | ["p"] if step > 0 => step -= 1,
+            ["prev"] | ["p"] => writeln!(out, "That's the start.")?,
+            ["goto", n] => match n.parse::<usize>() {
+                Ok(n) if n <= last => step = n,
+                _ => writeln!(out, "Moves go from 0 to {}", last)?,
+            },
+            ["quit"] | ["exit"] => break,
+            _ => writeln!(out, "Commands: next (or Enter), prev, goto N, quit")?,
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+#[test]
+fn test_parse_command() {
+    let tl = Position(RowId::Top, ColumnId::Left);
+    let br = Position(RowId::Bottom, ColumnId::Right);
+    assert_eq!(parse_command("tl br"), Ok(Command::Toggle(vec![tl, br])));
+    assert_eq!(parse_command(" PLAY "), Ok(Command::Play));
+    assert_eq!(parse_command("trash br"), Ok(Command::Trash(br)));
+    assert_eq!(
+        parse_command("save 2"),
+        Ok(Command::Save(Slot::Numbered(2)))
+    );
+    assert_eq!(parse_command("bot"), Ok(Command::Bot));
+    assert!(parse_command("load 9").is_err());
+    assert!(parse_command("trash xx").is_err());
+    assert!(parse_command("tl zz").is_err());
+    assert!(parse_command("").is_err());
+}
+
+#[test]
+fn test_scripted_game() {
+    let mut g = Game::generate_with_seed(3);
+    let script = "trash tl\ntrash tc\ntrash tr\nundo\ngraveyard\nquit\nplay\n";
+    let mut out = Vec::new();
+    run(&mut g, script.as_bytes(), &mut out).unwrap();
+    assert_eq!(g.trashes, Trashes::One);
+    assert_eq!(g.spread.tl.len(), 7);
+    assert_eq!(g.spread.tc.len(), 8);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("Can't trash that: There are no trashes left"));
+    assert!(out.contains(&format!("  1. {} trashed from tl", g.discards()[0].card)));
+    assert_eq!(g.discards().len(), 1);
+    assert!(out.contains("Seed 3"));
+}
+
+#[test]
+fn test_replay() {
+    let src = "seed 3\ntrash tl +0\ntrash tc +0\nundo +0\npair tl tc\n";
+    let script = "next\n\nnext\nnext\nprev\ngoto 9\n";
+    let mut out = Vec::new();
+    run_replay(&replay::parse(src).unwrap(), script.as_bytes(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("Move 2 of 4: Trash tc +0"));
+    assert!(out.contains("Step 4 doesn't match the deal: tl tc isn't a Pair"));
+    assert!(out.contains("That's the last move."));
+    assert!(out.contains("Moves go from 0 to 3"));
+}
@@ -0,0 +1,332 @@
+/// Keyboard input, independent of which terminal backend it came from.
+use std::fmt;
+
+use crate::model::*;
+
+/// A key the game cares about. Backends translate their own key codes into
+/// these so every backend gets the same controls.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Key {
+    /// A letter (always lower case) or a digit from the top row.
+    Char(char),
+    /// A digit on the number pad.
+    Num(u8),
+    F(u8),
+    Space,
+    Enter,
+    Escape,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event {
+    Key(Key),
+    /// The window was closed or the player asked to quit.
+    Close,
+    /// Something happened that needs the screen redrawn, but no input.
+    Redraw,
+    /// The left mouse button was pressed over a cell.
+    Click(i32, i32),
+    /// The mouse moved onto a different cell.
+    MouseMove(i32, i32),
+}
+
+/// Somewhere to get input from.
+pub trait Events {
+    /// Block until something happens.
+    fn wait_event(&mut self) -> Event;
+}
+
+/// Keys the UI loop handles itself, which can't be bound to anything else.
+pub const RESERVED: [(Key, &str); 8] = [
+    (Key::Char('h'), "help"),
+    (Key::Char('b'), "buried cards"),
+    (Key::Char('g'), "the graveyard"),
+    (Key::Char('t'), "statistics"),
+    (Key::F(5), "saving"),
+    (Key::F(6), "saving a replay"),
+    (Key::F(9), "loading"),
+    (Key::Escape, "quitting"),
+];
+
+/// What each message is called in `[autosage.keys]`.
+pub const ACTIONS: [(&str, Msg); 15] = [
+    ("tl", Msg::ToggleStack(Position(RowId::Top, ColumnId::Left))),
+    (
+        "tc",
+        Msg::ToggleStack(Position(RowId::Top, ColumnId::Center)),
+    ),
+    (
+        "tr",
+        Msg::ToggleStack(Position(RowId::Top, ColumnId::Right)),
+    ),
+    (
+        "ml",
+        Msg::ToggleStack(Position(RowId::Middle, ColumnId::Left)),
+    ),
+    (
+        "mc",
+        Msg::ToggleStack(Position(RowId::Middle, ColumnId::Center)),
+    ),
+    (
+        "mr",
+        Msg::ToggleStack(Position(RowId::Middle, ColumnId::Right)),
+    ),
+    (
+        "bl",
+        Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Left)),
+    ),
+    (
+        "bc",
+        Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Center)),
+    ),
+    (
+        "br",
+        Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Right)),
+    ),
+    ("play", Msg::MakeMove),
+    ("new", Msg::NewGame),
+    ("restart", Msg::RestartDeal),
+    ("undo", Msg::Undo),
+    ("redo", Msg::Redo),
+    ("hint", Msg::Hint),
+];
+
+/// Which keys send which messages on the main screen. Keys that open other
+/// screens (help, save slots) are handled by the UI loop instead.
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyMap {
+    bindings: Vec<(Key, Msg)>,
+}
+
+impl Default for KeyMap {
+    /// QWE/ASD/ZXC for the stacks, with the number keys and number pad laid
+    /// out the same way.
+    fn default() -> Self {
+        let stacks = [
+            ('q', 7, "tl"),
+            ('w', 8, "tc"),
+            ('e', 9, "tr"),
+            ('a', 4, "ml"),
+            ('s', 5, "mc"),
+            ('d', 6, "mr"),
+            ('z', 1, "bl"),
+            ('x', 2, "bc"),
+            ('c', 3, "br"),
+        ];
+        let mut bindings = Vec::new();
+        for (letter, digit, name) in stacks.iter() {
+            let msg = action(name).unwrap();
+            bindings.push((Key::Char(*letter), msg));
+            bindings.push((Key::Num(*digit), msg));
+            bindings.push((Key::Char((b'0' + digit) as char), msg));
+        }
+        bindings.extend_from_slice(&[
+            (Key::Space, Msg::MakeMove),
+            (Key::Char('p'), Msg::NewGame),
+            (Key::Char('u'), Msg::Undo),
+            (Key::Char('y'), Msg::Redo),
+            (Key::Char('r'), Msg::RestartDeal),
+            (Key::Char('i'), Msg::Hint),
+        ]);
+        KeyMap { bindings }
+    }
+}
+
+fn action(name: &str) -> Option<Msg> {
+    ACTIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, msg)| *msg)
+}
+
+fn action_name(msg: Msg) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|(_, m)| *m == msg)
+        .map(|(n, _)| *n)
+        .unwrap()
+}
+
+impl KeyMap {
+    /// What a key does on the main screen.
+    pub fn msg(&self, k: Key) -> Option<Msg> {
+        self.bindings
+            .iter()
+            .find(|(key, _)| *key == k)
+            .map(|(_, msg)| *msg)
+    }
+
+    /// The keys that send `msg`, in the order they were given.
+    pub fn keys(&self, msg: Msg) -> impl Iterator<Item = Key> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, m)| *m == msg)
+            .map(|(key, _)| *key)
+    }
+
+    /// Rebind actions, e.g. `("undo", "u, backspace")`. Each action given
+    /// replaces all of its keys; an empty list unbinds it. It's an error for
+    /// a key to do two things.
+    pub fn with_bindings(&self, entries: &[(String, String)]) -> Result<KeyMap, String> {
+        let mut rebound = Vec::new();
+        let mut added = Vec::new();
+        for (name, keys) in entries {
+            let msg = action(name).ok_or_else(|| format!("unknown action '{}'", name))?;
+            if rebound.contains(&msg) {
+                return Err(format!("'{}' is bound more than once", name));
+            }
+            rebound.push(msg);
+            for key in keys.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+                let k = parse_key(key).ok_or_else(|| format!("unknown key '{}'", key))?;
+                added.push((k, msg));
+            }
+        }
+        let mut bindings: Vec<(Key, Msg)> = self
+            .bindings
+            .iter()
+            .filter(|(_, msg)| !rebound.contains(msg))
+            .copied()
+            .collect();
+        for (k, msg) in added {
+            if let Some((_, what)) = RESERVED.iter().find(|(key, _)| *key == k) {
+                return Err(format!("{} is already used for {}", k, what));
+            }
+            if let Some((_, other)) = bindings.iter().find(|(key, _)| *key == k) {
+                return Err(format!(
+                    "{} is bound to both {} and {}",
+                    k,
+                    action_name(*other),
+                    action_name(msg)
+                ));
+            }
+            bindings.push((k, msg));
+        }
+        Ok(KeyMap { bindings })
+    }
+}
+
+/// Read a key's name from the config file: a letter or digit, `num0` to
+/// `num9` for the number pad, `f1` to `f12`, or one of `space`, `enter`,
+/// `backspace`, `left`, `right`, `up` and `down`.
+pub fn parse_key(src: &str) -> Option<Key> {
+    let src = src.to_lowercase();
+    let mut chars = src.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c)).filter(|_| c.is_ascii_alphanumeric());
+    }
+    let numbered = |prefix: &str, max: u8| {
+        src.strip_prefix(prefix)
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| *n <= max)
+    };
+    if let Some(n) = numbered("num", 9) {
+        return Some(Key::Num(n));
+    }
+    if let Some(n) = numbered("f", 12).filter(|n| *n > 0) {
+        return Some(Key::F(n));
+    }
+    match src.as_str() {
+        "space" | "spc" => Some(Key::Space),
+        "enter" => Some(Key::Enter),
+        "esc" | "escape" => Some(Key::Escape),
+        "backspace" => Some(Key::Backspace),
+        "left" => Some(Key::Left),
+        "right" => Some(Key::Right),
+        "up" => Some(Key::Up),
+        "down" => Some(Key::Down),
+        _ => None,
+    }
+}
+
+/// Keys are shown the way they're printed on a keyboard, e.g. `Q`, `SPC`.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            Key::Num(n) => write!(f, "Num{}", n),
+            Key::F(n) => write!(f, "F{}", n),
+            Key::Space => write!(f, "SPC"),
+            Key::Enter => write!(f, "Enter"),
+            Key::Escape => write!(f, "Esc"),
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+        }
+    }
+}
+
+/// Which save slot a key picks, if any.
+pub fn parse_slot(k: Key) -> Option<usize> {
+    match k {
+        Key::Char(c) => c.to_digit(10).map(|d| d as usize),
+        Key::Num(n) => Some(n as usize),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_default_keys() {
+    let keys = KeyMap::default();
+    let tl = Msg::ToggleStack(Position(RowId::Top, ColumnId::Left));
+    assert_eq!(keys.msg(Key::Char('q')), Some(tl));
+    assert_eq!(keys.msg(Key::Num(7)), Some(tl));
+    assert_eq!(keys.msg(Key::Char('7')), Some(tl));
+    assert_eq!(keys.msg(Key::Space), Some(Msg::MakeMove));
+    assert_eq!(keys.msg(Key::Char('h')), None);
+    assert_eq!(
+        keys.keys(Msg::Undo).collect::<Vec<_>>(),
+        vec![Key::Char('u')]
+    );
+}
+
+#[test]
+fn test_rebinding() {
+    let entries = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, k)| (a.to_string(), k.to_string()))
+            .collect()
+    };
+    let keys = KeyMap::default()
+        .with_bindings(&entries(&[("undo", "Backspace, z"), ("bl", "num1, v")]))
+        .unwrap();
+    assert_eq!(keys.msg(Key::Backspace), Some(Msg::Undo));
+    assert_eq!(keys.msg(Key::Char('z')), Some(Msg::Undo));
+    assert_eq!(keys.msg(Key::Char('u')), None);
+    assert_eq!(keys.msg(Key::Char('1')), None);
+    assert_eq!(
+        keys.keys(Msg::Undo).collect::<Vec<_>>(),
+        vec![Key::Backspace, Key::Char('z')]
+    );
+
+    let err = |pairs| {
+        KeyMap::default()
+            .with_bindings(&entries(pairs))
+            .unwrap_err()
+    };
+    assert_eq!(err(&[("undo", "q")]), "Q is bound to both tl and undo");
+    assert_eq!(err(&[("hint", "h")]), "H is already used for help");
+    assert_eq!(err(&[("jump", "j")]), "unknown action 'jump'");
+    assert_eq!(err(&[("undo", "num10")]), "unknown key 'num10'");
+    assert_eq!(
+        err(&[("undo", "j"), ("undo", "k")]),
+        "'undo' is bound more than once"
+    );
+}
+
+#[test]
+fn test_key_names() {
+    for name in &["q", "7", "num7", "f12", "space", "left"] {
+        let key = parse_key(name).unwrap();
+        assert_eq!(parse_key(&key.to_string()), Some(key));
+    }
+    assert_eq!(parse_key("F0"), None);
+    assert_eq!(parse_key("?"), None);
+}
@@ -0,0 +1,95 @@
+/// Where the clickable parts of the main screen are. Drawing and working out
+/// what the mouse is pointing at both go through here, so they can't drift
+/// apart.
+use crate::model::*;
+
+/// Wide enough for the tallest stack, e.g. `8| 10♠`.
+pub const STACK_WIDTH: i32 = 6;
+
+/// Where the selected move is described.
+pub const MOVE_TEXT: (i32, i32) = (3, 15);
+pub const MOVE_TEXT_WIDTH: i32 = 28;
+
+/// Where the summary of a finished game starts, and how many rows it takes.
+pub const GAME_OVER_TOP: i32 = 12;
+pub const GAME_OVER_ROWS: i32 = 9;
+
+/// Where a hint is explained, and how many lines it can wrap to. It has
+/// rows of its own so a notice never lands on top of it.
+pub const HINT: (i32, i32) = (3, 17);
+pub const HINT_LINES: i32 = 4;
+
+/// Where messages for the player go while a game is in progress, and how
+/// many lines of how many characters they and hints can wrap to.
+pub const NOTICE: (i32, i32) = (3, HINT.1 + HINT_LINES);
+pub const NOTICE_WIDTH: i32 = 28;
+pub const NOTICE_LINES: i32 = 3;
+
+/// Where messages go once the game is over, below its summary.
+pub const GAME_OVER_NOTICE: (i32, i32) = (3, GAME_OVER_TOP + GAME_OVER_ROWS);
+
+/// The reminder of how to get help.
+pub const HELP_ROW: i32 = 24;
+
+/// Something on screen that can be clicked.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    Stack(Position),
+    Move,
+}
+
+/// The top left corner of a stack.
+pub fn stack_origin(Position(rowid, colid): Position) -> (i32, i32) {
+    let y = match rowid {
+        RowId::Top => 3,
+        RowId::Middle => 6,
+        RowId::Bottom => 9,
+    };
+    let x = match colid {
+        ColumnId::Left => 3,
+        ColumnId::Center => 10,
+        ColumnId::Right => 17,
+    };
+    (x, y)
+}
+
+/// What's at a cell on the screen, if it's anything that can be clicked.
+pub fn target_at(x: i32, y: i32) -> Option<Target> {
+    let on_stack = PILE_SIZES.iter().map(|(pos, _)| *pos).find(|pos| {
+        let (left, top) = stack_origin(*pos);
+        y == top && (left..left + STACK_WIDTH).contains(&x)
+    });
+    if let Some(pos) = on_stack {
+        return Some(Target::Stack(pos));
+    }
+    let (left, top) = MOVE_TEXT;
+    if y == top && (left..left + MOVE_TEXT_WIDTH).contains(&x) {
+        return Some(Target::Move);
+    }
+    None
+}
+
+#[test]
+fn test_target_at() {
+    for (pos, _) in PILE_SIZES.iter() {
+        let (x, y) = stack_origin(*pos);
+        assert_eq!(target_at(x, y), Some(Target::Stack(*pos)));
+        assert_eq!(target_at(x + STACK_WIDTH - 1, y), Some(Target::Stack(*pos)));
+        assert_eq!(target_at(x - 1, y), None);
+        assert_eq!(target_at(x, y + 1), None);
+    }
+    assert_eq!(target_at(MOVE_TEXT.0 + 4, MOVE_TEXT.1), Some(Target::Move));
+    assert_eq!(target_at(60, 3), None);
+}
+
+#[test]
+fn test_notices_fit() {
+    let (_, bottom_stacks) = stack_origin(Position(RowId::Bottom, ColumnId::Left));
+    assert!(bottom_stacks < GAME_OVER_TOP);
+    // The move and its breakdown take two rows
+    assert!(MOVE_TEXT.1 + 2 <= HINT.1);
+    assert!(HINT.1 + HINT_LINES <= NOTICE.1);
+    assert!(NOTICE.1 + NOTICE_LINES <= HELP_ROW);
+    assert!(GAME_OVER_TOP + GAME_OVER_ROWS <= GAME_OVER_NOTICE.1);
+    assert!(GAME_OVER_NOTICE.1 + NOTICE_LINES <= HELP_ROW);
+}
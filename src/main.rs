@@ -1,109 +1,296 @@
-use bear_lib_terminal::terminal;
-
-mod model;
+mod ansi;
+#[cfg(feature = "bear-lib-terminal")]
+mod blt;
+mod config;
+mod daily;
+mod headless;
+mod input;
+mod layout;
 mod render;
-mod update;
+mod replay;
+mod saves;
+mod stats;
+mod ui;
 
-fn parse_msg(k: terminal::KeyCode) -> Option<model::Msg> {
-    use model::*;
-    use terminal::KeyCode;
-    match k {
-        KeyCode::Q | KeyCode::Num7 => Some(Msg::ToggleStack(Position(RowId::Top, ColumnId::Left))),
-        KeyCode::W | KeyCode::Num8 => {
-            Some(Msg::ToggleStack(Position(RowId::Top, ColumnId::Center)))
-        }
-        KeyCode::E | KeyCode::Num9 => Some(Msg::ToggleStack(Position(RowId::Top, ColumnId::Right))),
-        KeyCode::A | KeyCode::Num4 => {
-            Some(Msg::ToggleStack(Position(RowId::Middle, ColumnId::Left)))
-        }
-        KeyCode::S | KeyCode::Num5 => {
-            Some(Msg::ToggleStack(Position(RowId::Middle, ColumnId::Center)))
-        }
-        KeyCode::D | KeyCode::Num6 => {
-            Some(Msg::ToggleStack(Position(RowId::Middle, ColumnId::Right)))
-        }
-        KeyCode::Z | KeyCode::Num1 => {
-            Some(Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Left)))
-        }
-        KeyCode::X | KeyCode::Num2 => {
-            Some(Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Center)))
-        }
-        KeyCode::C | KeyCode::Num3 => {
-            Some(Msg::ToggleStack(Position(RowId::Bottom, ColumnId::Right)))
-        }
-        KeyCode::Space => Some(Msg::MakeMove),
-        KeyCode::P => Some(Msg::NewGame),
-        KeyCode::Escape => {
-            terminal::close();
-            None
-        }
-        KeyCode::H => {
-            render_help();
-            None
+use autosage_engine::{hint, model, odds, rules, solver, strategy, update};
+
+#[derive(PartialEq)]
+enum Frontend {
+    Window,
+    Ansi,
+    Headless,
+}
+
+struct Args {
+    seed: Option<u64>,
+    /// A deal given with `--deal`, in `notation` form.
+    deal: Option<model::Game>,
+    frontend: Frontend,
+    solve: bool,
+    replay: Option<String>,
+    /// Rules picked with `--rules`, for a new game.
+    rules: Option<rules::Ruleset>,
+    daily: bool,
+}
+
+/// Read the command line, exiting with a usage message if the arguments
+/// don't make sense.
+fn parse_args() -> Args {
+    let default_frontend = if cfg!(feature = "bear-lib-terminal") {
+        Frontend::Window
+    } else {
+        Frontend::Headless
+    };
+    let mut parsed = Args {
+        seed: None,
+        deal: None,
+        frontend: default_frontend,
+        solve: false,
+        replay: None,
+        rules: None,
+        daily: false,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => match args.next().map(|s| s.parse::<u64>()) {
+                Some(Ok(s)) => parsed.seed = Some(s),
+                _ => usage_and_exit("--seed needs a whole number"),
+            },
+            "--deal" => match args.next().map(|s| model::Game::from_notation(&s)) {
+                Some(Ok(g)) => parsed.deal = Some(g),
+                Some(Err(e)) => usage_and_exit(&e.to_string()),
+                None => usage_and_exit("--deal needs a deal"),
+            },
+            "--daily" => parsed.daily = true,
+            "--headless" => parsed.frontend = Frontend::Headless,
+            "--ansi" => parsed.frontend = Frontend::Ansi,
+            "--solve" => parsed.solve = true,
+            "--rules" => match args.next().map(|s| rules::Ruleset::preset(&s)) {
+                Some(Some(r)) => parsed.rules = Some(r),
+                _ => usage_and_exit(&format!(
+                    "--rules needs one of {}",
+                    rules::PRESETS.join(", ")
+                )),
+            },
+            "--replay" => match args.next() {
+                Some(file) => parsed.replay = Some(file),
+                None => usage_and_exit("--replay needs a file"),
+            },
+            _ => usage_and_exit(&format!("Unknown argument '{}'", arg)),
         }
-        _ => None,
     }
+    if parsed.daily && (parsed.seed.is_some() || parsed.deal.is_some() || parsed.rules.is_some()) {
+        usage_and_exit("--daily picks its own deal and rules");
+    }
+    parsed
 }
 
-fn render_help() {
-    terminal::clear(None);
-    let msg = r#"
-    Auto Sage
+fn usage_and_exit(problem: &str) -> ! {
+    eprintln!("{}", problem);
+    eprintln!(
+        "usage: autosage [--seed N | --deal DEAL | --daily | --replay FILE] [--rules {}] [--ansi | --headless | --solve]",
+        rules::PRESETS.join("|")
+    );
+    std::process::exit(2);
+}
 
-    Select cards with       Play move with
-    Q W E                   SPC
-    A S D  or NumPad
-    Z X C
+impl Args {
+    /// The deal asked for on the command line, if there was one. Asking for
+    /// rules without a deal starts a new game under them.
+    fn fresh_game(&mut self) -> Option<model::Game> {
+        let seed = self.seed;
+        let fresh = self
+            .deal
+            .take()
+            .or_else(|| seed.map(model::Game::generate_with_seed));
+        match self.rules.take() {
+            Some(rules) => Some(
+                fresh
+                    .unwrap_or_else(model::Game::generate)
+                    .with_rules(rules)
+                    .expect("Every preset deals the whole deck"),
+            ),
+            None => fresh,
+        }
+    }
+}
 
-    Quit with               New Game
-    Esc                     P
+/// The game to play: `fresh` if there is one, or else whatever was autosaved
+/// last time. Also returns a message for the player if the autosave couldn't
+/// be loaded, after the config's `warning` if there is one.
+fn start_game(
+    fresh: Option<model::Game>,
+    cfg: &config::Config,
+    warning: Option<String>,
+) -> (model::Game, Option<String>) {
+    let mut notices: Vec<String> = warning.into_iter().collect();
+    let mut g = match fresh {
+        Some(g) => g,
+        None => match saves::load(saves::Slot::Auto) {
+            Ok(Some(g)) => g,
+            Ok(None) => model::Game::generate(),
+            Err(e) => {
+                notices.push(format!("Started a new game. {}", e));
+                model::Game::generate()
+            }
+        },
+    };
+    g.set_undo_limit(cfg.undo_limit);
+    let notice = Some(notices.join(" ")).filter(|n| !n.is_empty());
+    (g, notice)
+}
 
-    Press any key to return
+fn run_headless(fresh: Option<model::Game>, cfg: &config::Config, warning: Option<String>) {
+    let (mut g, notice) = start_game(fresh, cfg, warning);
+    if let Some(msg) = notice {
+        eprintln!("{}", msg);
+    }
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    if let Err(e) = headless::run(&mut g, stdin.lock(), stdout.lock()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = saves::save(&g, saves::Slot::Auto) {
+        eprintln!("{}", e);
+    }
+}
 
-    Based on Sage Solitaire by Zach Gage
-    sagesolitaire.com
+/// Print the best line of play for a deal.
+fn run_solver(fresh: Option<model::Game>) {
+    let mut g = fresh.unwrap_or_else(model::Game::generate);
+    let solution = solver::solve(&g);
+    if g.is_custom_deal() {
+        println!("Deal {}", g.to_notation());
+    } else {
+        println!("Seed {}", g.seed());
+    }
+    if !g.rules().is_standard() {
+        println!("Rules {}", g.rules().name);
+    }
+    for (idx, (mv, positions)) in (1..).zip(solution.moves) {
+        let before = g.score();
+        let names: Vec<String> = model::PILE_SIZES
+            .iter()
+            .map(|(pos, _)| *pos)
+            .filter(|pos| positions.contains(pos))
+            .map(|pos| pos.to_string())
+            .collect();
+        g.selected = positions;
+        update::update(model::Msg::MakeMove, &mut g).expect("The solver only finds legal moves");
+        let what = match mv {
+            model::Move::Trash(_) => "Trash".to_owned(),
+            model::Move::PlayHand(h) => h.to_string(),
+        };
+        println!(
+            "{: >3}. {: <20} {: <15} {: >+4}",
+            idx,
+            what,
+            names.join(" "),
+            g.score() - before
+        );
+    }
+    println!(
+        "Best score {} ({} from hands, {} from piles); {} positions searched",
+        solution.score,
+        g.hand_score(),
+        g.pile_bonus(),
+        solution.nodes
+    );
+}
 
-    written by Nathaniel Knight
-    nathanielknight.ca
+/// Step through a replay file instead of playing.
+fn run_replay(file: &str, frontend: Frontend, keys: &input::KeyMap) {
+    let r = match replay::load(file.as_ref()) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    match frontend {
+        Frontend::Headless => {
+            let stdin = std::io::stdin();
+            let stdout = std::io::stdout();
+            if let Err(e) = headless::run_replay(&r, stdin.lock(), stdout.lock()) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Frontend::Ansi => match ansi::Ansi::open() {
+            Ok(mut term) => ui::show_replay(&mut term, &r, keys),
+            Err(e) => {
+                eprintln!("Couldn't set up the terminal: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Frontend::Window => {
+            #[cfg(feature = "bear-lib-terminal")]
+            ui::show_replay(&mut blt::Blt::open(), &r, keys);
+        }
+    }
+}
 
-    Built with BearLibTerminal
-    http://foo.wyrd.name/en:bearlibterminal
-    "#;
-    terminal::print_xy(3, 3, msg);
-    terminal::refresh();
-    terminal::wait_event();
+fn run_ui<T: ui::Terminal>(
+    term: &mut T,
+    fresh: Option<model::Game>,
+    cfg: &config::Config,
+    warning: Option<String>,
+) {
+    let (mut g, notice) = start_game(fresh, cfg, warning);
+    ui::run(term, &mut g, notice, cfg);
+    if let Err(e) = saves::save(&g, saves::Slot::Auto) {
+        eprintln!("{}", e);
+    }
 }
 
 fn main() {
-    let mut g = model::Game::generate();
-
-    terminal::open("Auto-Sage", 52, 27);
-
-    render::draw_game(&g);
-    terminal::refresh();
-
-    loop {
-        let t_evt = match terminal::wait_event() {
-            Some(e) => e,
-            None => continue,
-        };
-        match t_evt {
-            terminal::Event::Close => break, // leave main loop on quit
-            terminal::Event::KeyPressed {
-                key: k,
-                ctrl: _,
-                shift: _,
-            } => {
-                // If a key was pressed that matches an input method, update the game
-                if let Some(msg) = parse_msg(k) {
-                    update::update(msg, &mut g);
-                }
+    let mut args = parse_args();
+    let (cfg, mut warning) = config::Config::load();
+    if args.daily && args.solve {
+        // Solving doesn't use up the day's attempt
+        args.deal = Some(daily::game(daily::Date::today()));
+    } else if args.daily {
+        match daily::start(daily::Date::today()) {
+            Ok((g, msg)) => {
+                args.deal = Some(g);
+                warning = Some(match warning {
+                    Some(w) => format!("{} {}", w, msg),
+                    None => msg,
+                });
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.solve || args.replay.is_some() {
+        if let Some(w) = &warning {
+            eprintln!("{}", w);
+        }
+    }
+    if args.solve {
+        run_solver(args.fresh_game());
+        return;
+    }
+    if let Some(file) = args.replay {
+        run_replay(&file, args.frontend, &cfg.keys);
+        return;
+    }
+    match args.frontend {
+        Frontend::Headless => run_headless(args.fresh_game(), &cfg, warning),
+        Frontend::Ansi => match ansi::Ansi::open() {
+            Ok(mut term) => run_ui(&mut term, args.fresh_game(), &cfg, warning),
+            Err(e) => {
+                eprintln!("Couldn't set up the terminal: {}", e);
+                std::process::exit(1);
             }
-            _ => continue, // Ignore other messages
+        },
+        Frontend::Window => {
+            #[cfg(feature = "bear-lib-terminal")]
+            run_ui(&mut blt::Blt::open(), args.fresh_game(), &cfg, warning);
         }
-        terminal::clear(None);
-        render::draw_game(&g);
-        terminal::refresh();
     }
-    terminal::close();
 }
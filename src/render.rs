@@ -1,116 +1,138 @@
 use std::collections::HashSet;
-use std::fmt;
-
-use bear_lib_terminal::{terminal, Color};
 
+use crate::hint;
+use crate::input::{Key, KeyMap, RESERVED};
+use crate::layout::{self, Target};
 use crate::model::*;
+use crate::odds::{self, Buried, Chance, Odds};
+use crate::rules::Ruleset;
+use crate::saves::SaveError;
+use crate::stats::Summary;
 
+pub const WIDTH: i32 = 72;
+pub const HEIGHT: i32 = 27;
 
 const DATA_LEFT: i32 = 33;
+const ODDS_LEFT: i32 = 54;
 
-// ---------------------------------------------
-// Format data
+pub type Rgb = (u8, u8, u8);
 
-impl Into<String> for Rank {
-    fn into(self) -> String {
-        let c = match self {
-            Rank::Ace => "A",
-            Rank::Two => "2",
-            Rank::Three => "3",
-            Rank::Four => "4",
-            Rank::Five => "5",
-            Rank::Six => "6",
-            Rank::Seven => "7",
-            Rank::Eight => "8",
-            Rank::Nine => "9",
-            Rank::Ten => "10",
-            Rank::Jack => "J",
-            Rank::Queen => "Q",
-            Rank::King => "K",
-        };
-        c.to_owned()
-    }
+pub const BLACK: Rgb = (0, 0, 0);
+
+/// A screen to draw the game on.
+///
+/// Text passed to `print_xy` may contain newlines, which start the next line
+/// back at `x`, and BearLibTerminal-style colour markup:
+/// `[color=R,G,B]...[/color]`.
+pub trait Renderer {
+    fn clear(&mut self);
+    fn print_xy(&mut self, x: i32, y: i32, text: &str);
+    /// Set the background colour for everything printed from now on.
+    fn set_background(&mut self, bg: Rgb);
+    fn refresh(&mut self);
 }
 
-impl fmt::Display for Rank {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let r = self.clone();
-        let s: String = r.into();
-        write!(f, "{: >2}", s)
-    }
+// ---------------------------------------------
+// Format data
+
+/// A card in its suit's colour.
+fn colored(c: Card) -> String {
+    let Card(_, s) = c;
+    let colo = match s {
+        Suit::Club => "70,224,53",
+        Suit::Diamond => "237,237,113",
+        Suit::Heart => "226,99,99",
+        Suit::Spade => "75,134,239",
+    };
+    format!("[color={}]{}[/color]", colo, c)
 }
 
-impl fmt::Display for Suit {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let c = match self {
-            Suit::Club => '♣',
-            Suit::Diamond => '♦',
-            Suit::Heart => '♥',
-            Suit::Spade => '♠',
-        };
-        write!(f, "{}", c)
+/// Break plain text into lines of at most `width` characters.
+fn wrap(text: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
     }
+    lines.push(line);
+    lines.join("\n")
 }
 
-impl fmt::Display for Card {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let Card(r, s) = self;
-        let colo = match s {
-            Suit::Club => "70,224,53",
-            Suit::Diamond => "237,237,113",
-            Suit::Heart => "226,99,99",
-            Suit::Spade => "75,134,239",
-        };
-        write!(f, "[color={}]{: >2}{}[/color]", colo, r, s)
+/// A chance as a percentage, `width` characters wide, in red if the hand is
+/// dead.
+fn percent(c: Chance, width: usize) -> String {
+    if c.dead {
+        format!("[color=226,99,99]{: >w$}[/color]", "--", w = width)
+    } else {
+        format!("{: >w$}", (c.p * 100.0).round() as u32, w = width)
     }
 }
 
-impl fmt::Display for Hand {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let msg = match self {
-            Hand::Pair => "Pair",
-            Hand::ThreeOfAKind => "Three of a Kind",
-            Hand::StraightThree => "Three-Card Straight",
-            Hand::FourOfAKind => "Four of a Kind",
-            Hand::FullHouse => "Full House",
-            Hand::StraightFive => "Five-Card Straight",
-            Hand::Flush => "Flush",
-            Hand::StraightFlush => "Straight Flush!",
-        };
-        write!(f, "{}", msg)
+/// Short enough to fit in the odds panel.
+fn short_name(h: Hand) -> &'static str {
+    match h {
+        Hand::Pair => "Pair",
+        Hand::StraightThree => "3-Straight",
+        Hand::ThreeOfAKind => "3 of a Kind",
+        Hand::StraightFive => "5-Straight",
+        Hand::FullHouse => "Full House",
+        Hand::Flush => "Flush",
+        Hand::FourOfAKind => "4 of a Kind",
+        Hand::StraightFlush => "Str. Flush",
     }
 }
 
 // ---------------------------------------------
 // Draw feedback column
 
-fn draw_score(score: u32) {
+fn draw_score(r: &mut dyn Renderer, score: u32) {
     let scr_msg = format!("Score:{: >10}", score);
-    terminal::print_xy(DATA_LEFT, 3, &scr_msg);
+    r.print_xy(DATA_LEFT, 3, &scr_msg);
 }
 
-fn draw_trashes(t: &Trashes) {
+fn draw_trashes(r: &mut dyn Renderer, t: &Trashes) {
     let msg = match &t {
         Trashes::None => "",
         Trashes::One => "•",
         Trashes::Two => "••",
     };
 
-    terminal::print_xy(DATA_LEFT, 5, &format!("Trashes:      {: >2}", msg));
+    r.print_xy(DATA_LEFT, 5, &format!("Trashes:      {: >2}", msg));
 }
 
-fn draw_bonus(c: Card) {
-    let msg = format!("Bonus suit:  {}", c);
-    terminal::print_xy(DATA_LEFT, 7, &msg);
+fn draw_bonus(r: &mut dyn Renderer, c: Card) {
+    let msg = format!("Bonus suit:  {}", colored(c));
+    r.print_xy(DATA_LEFT, 7, &msg);
 }
 
+fn draw_seed(r: &mut dyn Renderer, g: &Game) {
+    let seed = if g.is_custom_deal() {
+        "custom".to_owned()
+    } else {
+        g.seed().to_string()
+    };
+    let msg = format!("[color=128,128,128]Seed:{: >14}[/color]", seed);
+    r.print_xy(DATA_LEFT, 25, &msg);
+    if !g.rules().is_standard() {
+        let msg = format!("[color=128,128,128]Rules:{: >12}[/color]", g.rules().name);
+        r.print_xy(ODDS_LEFT, 25, &msg);
+    }
+}
 
-fn draw_remaining(rem_cards: &HashSet<Card>) {
+/// The remaining cards of each rank, with the chance of a pair, three or
+/// four of that rank.
+fn draw_remaining(r: &mut dyn Renderer, rem_cards: &HashSet<Card>, odds: &Odds) {
     const TOP: i32 = 9;
-    terminal::print_xy(DATA_LEFT, TOP, "Remaining Cards:");
-    terminal::print_xy(DATA_LEFT, TOP + 2, "   ♣♦♥♠");
-    let mut idx: usize = 1;
-    for &rank in &RANKS {
+    r.print_xy(DATA_LEFT, TOP, "Remaining Cards:");
+    r.print_xy(DATA_LEFT, TOP + 2, "   ♣♦♥♠  2x  3x  4x");
+    for (idx, (&rank, kinds)) in (1..).zip(RANKS.iter().zip(odds.of_a_kind.iter())) {
         let cdhs = [
             rem_cards.contains(&Card(rank, Suit::Club)),
             rem_cards.contains(&Card(rank, Suit::Diamond)),
@@ -119,69 +141,440 @@ fn draw_remaining(rem_cards: &HashSet<Card>) {
         ];
         let cdhs_s: String = cdhs.iter().map(|i| if *i { '•' } else { ' ' }).collect();
         let rank_s: String = rank.into();
-        let row: String = format!("{: >2} {}", rank_s, cdhs_s);
-        terminal::print_xy(DATA_LEFT, TOP + 2 + idx as i32, &row);
-        idx += 1;
+        let kinds_s: String = kinds.iter().map(|c| percent(*c, 4)).collect();
+        let row: String = format!("{: >2} {}{}", rank_s, cdhs_s, kinds_s);
+        r.print_xy(DATA_LEFT, TOP + 2 + idx, &row);
     }
 }
 
-fn draw_stack(Position(rowid, colid): Position, stack: &CardStack, selected: bool) {
-    let y = match rowid {
-        RowId::Top => 3,
-        RowId::Middle => 6,
-        RowId::Bottom => 9,
-    };
-    let x = match colid {
-        ColumnId::Left => 3,
-        ColumnId::Center => 10,
-        ColumnId::Right => 17,
-    };
+/// How likely each hand is to still be playable, as a percentage.
+pub fn draw_odds(r: &mut dyn Renderer, odds: &Odds) {
+    r.print_xy(ODDS_LEFT, 3, "Hand Odds (%):");
+    for (idx, (&h, c)) in (0..).zip(odds::HANDS.iter().zip(odds.hands.iter())) {
+        let row = format!("{: <14}{}", short_name(h), percent(*c, 4));
+        r.print_xy(ODDS_LEFT, 5 + idx, &row);
+    }
+    r.print_xy(ODDS_LEFT, 14, "Flush by suit:");
+    for (idx, (&suit, c)) in (0..).zip(SUITS.iter().zip(odds.flushes.iter())) {
+        let x = ODDS_LEFT + 9 * (idx % 2);
+        let y = 16 + idx / 2;
+        r.print_xy(x, y, &format!("{}{}", suit, percent(*c, 5)));
+    }
+}
+
+fn draw_stack(
+    r: &mut dyn Renderer,
+    pos @ Position(rowid, _): Position,
+    stack: &CardStack,
+    rules: &Ruleset,
+    selected: bool,
+    hovered: bool,
+) {
+    let (x, y) = layout::stack_origin(pos);
     match stack.last() {
         None => {
-            let bonus = rowid.bonus();
+            let bonus = rules.row_bonus(rowid);
             let msg = format!("[color=45,45,45]0|{: >+3}", bonus);
-            terminal::print_xy(x, y, &msg);
+            r.print_xy(x, y, &msg);
         }
         Some(card) => {
             if selected {
-                terminal::set_background(Color::from_rgb(70, 70, 70));
+                r.set_background((70, 70, 70));
+            } else if hovered {
+                r.set_background((55, 55, 55));
             } else {
-                terminal::set_background(Color::from_rgb(40, 40, 40));
+                r.set_background((40, 40, 40));
             }
-            terminal::print_xy(x, y, &format!("[color=0,0,0]{}|{}", stack.len(), card));
+            let msg = format!("[color=0,0,0]{}|{}", stack.len(), colored(*card));
+            r.print_xy(x, y, &msg);
         }
     }
     //reset default bg color
-    terminal::set_background(Color::from_rgb(0, 0, 0));
+    r.set_background(BLACK);
+}
+
+/// A move and what it would score, then how that adds up if there's more
+/// to it than the hand's points.
+pub fn describe_move(preview: &Preview) -> (String, Option<String>) {
+    let name = match preview.mv {
+        Move::Trash(_) => "Trash".to_owned(),
+        Move::PlayHand(h) => h.to_string(),
+    };
+    let total = preview.total();
+    if total == 0 {
+        return (name, None);
+    }
+    let headline = format!("{} (+{} pts)", name, total);
+    if total == preview.base {
+        return (headline, None);
+    }
+    let mut parts = Vec::new();
+    match preview.multiplier {
+        1 if preview.base > 0 => parts.push(preview.base.to_string()),
+        1 => (),
+        m => parts.push(format!("{} x{} bonus", preview.base, m)),
+    }
+    for (pos, bonus) in preview.cleared.iter() {
+        parts.push(format!("{} for {}", bonus, pos));
+    }
+    (headline, Some(parts.join(" + ")))
+}
+
+/// The selected move, which can be clicked to make it.
+fn draw_move(r: &mut dyn Renderer, preview: Option<Preview>, hovered: bool) {
+    if let Some(preview) = preview {
+        let (headline, breakdown) = describe_move(&preview);
+        let (x, y) = layout::MOVE_TEXT;
+        if hovered {
+            r.set_background((55, 55, 55));
+        }
+        r.print_xy(x, y, &headline);
+        r.set_background(BLACK);
+        if let Some(breakdown) = breakdown {
+            r.print_xy(
+                x,
+                y + 1,
+                &wrap(&breakdown, layout::MOVE_TEXT_WIDTH as usize),
+            );
+        }
+    }
 }
 
-fn draw_move(mv_opt: Option<Move>) {
-    if let Some(mv) = mv_opt {
-        let msg = match mv {
-            Move::Trash(_) => "Trash".to_owned(),
-            Move::PlayHand(h) => format!("{} ({} pts)", h, h.points()),
+/// Why the selection can't be played, where the move would be.
+fn draw_unplayable(r: &mut dyn Renderer, reason: Unplayable) {
+    let (x, y) = layout::MOVE_TEXT;
+    r.print_xy(
+        x,
+        y,
+        &wrap(&reason.to_string(), layout::MOVE_TEXT_WIDTH as usize),
+    );
+}
+
+fn draw_game_over(r: &mut dyn Renderer, g: &Game, keys: &KeyMap) {
+    const TOP: i32 = layout::GAME_OVER_TOP;
+    let headline = match g.status() {
+        Status::Won => "All piles cleared!",
+        _ => "No moves left",
+    };
+    r.print_xy(3, TOP, headline);
+    r.print_xy(3, TOP + 2, &format!("Hands:       {: >5}", g.hand_score()));
+    r.print_xy(3, TOP + 3, &format!("Pile bonus:  {: >5}", g.pile_bonus()));
+    r.print_xy(3, TOP + 4, &format!("Final score: {: >5}", g.score()));
+    r.print_xy(3, TOP + 5, &format!("Cards left:  {: >5}", g.cards_left()));
+    let undos = match g.undo_limit() {
+        Some(limit) => format!("{}/{}", g.undos_used(), limit),
+        None => format!("{}", g.undos_used()),
+    };
+    r.print_xy(3, TOP + 6, &format!("Undos used:  {: >5}", undos));
+    let new_game = format!("Press '{}' for a new game", key_for(keys, Msg::NewGame));
+    r.print_xy(3, TOP + 8, &new_game);
+}
+
+/// A message for the player, kept clear of the summary once the game is
+/// over. Anything past `layout::NOTICE_LINES` lines is cut off.
+pub fn draw_notice(r: &mut dyn Renderer, g: &Game, msg: &str) {
+    let (x, y) = match g.status() {
+        Status::InProgress => layout::NOTICE,
+        _ => layout::GAME_OVER_NOTICE,
+    };
+    print_wrapped(r, (x, y), layout::NOTICE_LINES, msg);
+}
+
+/// `msg` wrapped to `layout::NOTICE_WIDTH`, cut off after `lines` lines.
+fn print_wrapped(r: &mut dyn Renderer, (x, y): (i32, i32), lines: i32, msg: &str) {
+    let text = wrap(msg, layout::NOTICE_WIDTH as usize);
+    let kept: Vec<&str> = text.lines().take(lines as usize).collect();
+    r.print_xy(x, y, &kept.join("\n"));
+}
+
+pub fn draw_slot_menu(
+    r: &mut dyn Renderer,
+    title: &str,
+    slots: &[Result<Option<Game>, SaveError>],
+) {
+    r.print_xy(3, 3, title);
+    for (idx, slot) in (1..).zip(slots.iter()) {
+        let summary = match slot {
+            Ok(Some(g)) => format!(
+                "Seed {}, {} points, {} cards left",
+                g.seed(),
+                g.score(),
+                g.cards_left()
+            ),
+            Ok(None) => "[color=128,128,128]Empty[/color]".to_owned(),
+            Err(_) => "[color=226,99,99]Unreadable[/color]".to_owned(),
         };
-        terminal::print_xy(3, 15, &msg);
+        r.print_xy(3, 4 + 2 * idx, &format!("{}  {}", idx, summary));
+    }
+    r.print_xy(3, 18, "Press 1-5 to choose a slot");
+    r.print_xy(3, 19, "or any other key to cancel");
+}
+
+fn draw_help_msg(r: &mut dyn Renderer) {
+    r.print_xy(3, layout::HELP_ROW, "Press 'H' for help");
+}
+
+/// What could be under the top card of the selected stack, in place of the
+/// odds panel.
+pub fn draw_buried(r: &mut dyn Renderer, g: &Game) {
+    let pos = match g.selected.iter().collect::<Vec<_>>().as_slice() {
+        [pos] => **pos,
+        _ => {
+            let msg = "Select one stack to see what could be under it.";
+            r.print_xy(ODDS_LEFT, 3, &wrap(msg, 18));
+            return;
+        }
+    };
+    let b: Buried = odds::buried(&crate::strategy::View::of(g), pos);
+    r.print_xy(ODDS_LEFT, 3, &format!("Under {}: {} cards", pos, b.hidden));
+    let msg = format!(
+        "{} unseen cards, each {:.0}% likely",
+        b.unseen.len(),
+        b.each * 100.0
+    );
+    r.print_xy(ODDS_LEFT, 4, &wrap(&msg, 18));
+    r.print_xy(ODDS_LEFT, 7, "   ♣♦♥♠   ≥1");
+    for (idx, (&rank, p)) in (0..).zip(RANKS.iter().zip(b.by_rank.iter())) {
+        let dots: String = SUITS
+            .iter()
+            .map(|&suit| {
+                if b.unseen.contains(&Card(rank, suit)) {
+                    '•'
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        let rank_s: String = rank.into();
+        let row = format!("{: >2} {} {: >4.0}%", rank_s, dots, p * 100.0);
+        r.print_xy(ODDS_LEFT, 8 + idx, &row);
+    }
+    r.print_xy(ODDS_LEFT, 21, "Expected by suit:");
+    for (idx, (&suit, e)) in (0..).zip(SUITS.iter().zip(b.by_suit.iter())) {
+        let x = ODDS_LEFT + 9 * (idx % 2);
+        let y = 22 + idx / 2;
+        r.print_xy(x, y, &format!("{} {:.1}", suit, e));
     }
 }
 
-fn draw_help_msg() {
-    terminal::print_xy(3, 24, "Press 'H' for help");
+/// How many discards fit in the graveyard panel at once.
+pub const GRAVEYARD_ROWS: usize = 17;
+
+/// The cards taken off the spread, newest first, skipping the first
+/// `scroll` of them. Drawn in place of the odds panel.
+pub fn draw_graveyard(r: &mut dyn Renderer, g: &Game, scroll: usize) {
+    let discards = g.discards();
+    r.print_xy(ODDS_LEFT, 3, &format!("Graveyard ({}):", discards.len()));
+    if discards.is_empty() {
+        r.print_xy(ODDS_LEFT, 5, "No cards yet.");
+    }
+    let shown = discards.iter().rev().skip(scroll).take(GRAVEYARD_ROWS);
+    for (idx, d) in (0..).zip(shown) {
+        let cause = match d.cause {
+            Move::Trash(_) => "Trash",
+            Move::PlayHand(h) => short_name(h),
+        };
+        let row = format!("{: >2} {} {}", d.turn, colored(d.card), cause);
+        r.print_xy(ODDS_LEFT, 5 + idx, &row);
+    }
+    if discards.len() > GRAVEYARD_ROWS {
+        r.print_xy(ODDS_LEFT, 23, "Up/Down to scroll");
+    }
 }
 
-pub fn draw_game(g: &Game) {
+/// The main screen. `hover` is whatever the mouse is pointing at.
+pub fn draw_game(
+    r: &mut dyn Renderer,
+    g: &Game,
+    odds: &Odds,
+    keys: &KeyMap,
+    hover: Option<Target>,
+) {
     for rowid in &[RowId::Top, RowId::Middle, RowId::Bottom] {
         for colid in &[ColumnId::Left, ColumnId::Center, ColumnId::Right] {
             let pos = Position(*rowid, *colid);
             let stack = g.spread.get_stack(pos);
+            let hinted = match &g.hint {
+                Some((_, positions)) => positions.contains(&pos),
+                None => false,
+            };
             let selected = g.selected.contains(&pos);
-            draw_stack(pos, stack, selected);
+            let hovered = hover == Some(Target::Stack(pos));
+            draw_stack(r, pos, stack, g.rules(), selected || hinted, hovered);
         }
     }
-    draw_move(g.selected_move());
-    draw_help_msg();
-    draw_score(g.score());
-    draw_trashes(&g.trashes);
-    draw_bonus(g.bonus_card);
-    draw_remaining(&g.remaining_cards());
+    if g.status() == Status::InProgress {
+        draw_move(r, g.preview_move(), hover == Some(Target::Move));
+        if let Some(reason) = g.explain_selection() {
+            draw_unplayable(r, reason);
+        }
+        if let Some((mv, positions)) = &g.hint {
+            let msg = format!(
+                "Hint: {} Press '{}' again to select it.",
+                hint::explain(g, *mv, positions),
+                key_for(keys, Msg::Hint)
+            );
+            print_wrapped(r, layout::HINT, layout::HINT_LINES, &msg);
+        }
+    } else {
+        draw_game_over(r, g, keys);
+    }
+    draw_help_msg(r);
+    draw_score(r, g.score());
+    draw_trashes(r, &g.trashes);
+    draw_bonus(r, g.bonus_card);
+    draw_remaining(r, &g.remaining_cards(), odds);
+    draw_seed(r, g);
+}
+
+/// The first key bound to `msg`, or a dash if there isn't one.
+fn key_for(keys: &KeyMap, msg: Msg) -> String {
+    match keys.keys(msg).next() {
+        Some(k) => k.to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+pub fn draw_help(r: &mut dyn Renderer, keys: &KeyMap, rules: &Ruleset) {
+    let k = |msg| key_for(keys, msg);
+    let stack = |rowid, colid| k(Msg::ToggleStack(Position(rowid, colid)));
+    let row = |rowid| {
+        let names: Vec<String> = [ColumnId::Left, ColumnId::Center, ColumnId::Right]
+            .iter()
+            .map(|colid| stack(rowid, *colid))
+            .collect();
+        format!("{: <24}", names.join(" "))
+    };
+    let tl = Msg::ToggleStack(Position(RowId::Top, ColumnId::Left));
+    let numpad = if keys.msg(Key::Num(7)) == Some(tl) {
+        "or NumPad"
+    } else {
+        ""
+    };
+    let reserved: Vec<String> = RESERVED
+        .chunks(3)
+        .map(|line| {
+            let keys: Vec<String> = line
+                .iter()
+                .map(|(key, what)| format!("{: <24}", format!("{} {}", key, what)))
+                .collect();
+            format!("    {}", keys.join("").trim_end())
+        })
+        .collect();
+    let rules_text: Vec<String> = describe_rules(rules)
+        .iter()
+        .map(|line| format!("    {}", line))
+        .collect();
+    let msg = format!(
+        r#"    Auto Sage
+
+    Select cards with       Play move with          Hint
+    {}{: <24}{}
+    {}{}
+    {}
+
+    Undo / Redo             Restart this deal       New Game
+    {: <24}{: <24}{}
+
+{}
+
+    Click a stack to select it, or the move to play it.
+
+{}
+
+    Press any key to return
+
+    Based on Sage Solitaire by Zach Gage, sagesolitaire.com
+    Written by Nathaniel Knight, nathanielknight.ca
+    Built with BearLibTerminal, http://foo.wyrd.name/en:bearlibterminal
+    "#,
+        row(RowId::Top),
+        k(Msg::MakeMove),
+        k(Msg::Hint),
+        row(RowId::Middle),
+        numpad,
+        row(RowId::Bottom),
+        format!("{} / {}", k(Msg::Undo), k(Msg::Redo)),
+        k(Msg::RestartDeal),
+        k(Msg::NewGame),
+        reserved.join("\n"),
+        rules_text.join("\n"),
+    );
+    r.print_xy(3, 1, &msg);
+}
+
+/// The rules in effect, in four lines.
+pub fn describe_rules(rules: &Ruleset) -> Vec<String> {
+    let trashes = match rules.max_trashes {
+        Trashes::None => "no trashes",
+        Trashes::One => "1 trash",
+        Trashes::Two => "2 trashes",
+    };
+    let points: Vec<String> = odds::HANDS
+        .iter()
+        .map(|h| format!("{} {}", short_name(*h), rules.points(*h)))
+        .collect();
+    vec![
+        format!(
+            "Rules: {}, {}, hands from {}+ rows, bonus suit x{}",
+            rules.name, trashes, rules.min_rows, rules.bonus_multiplier
+        ),
+        points[..4].join("  "),
+        points[4..].join("  "),
+        format!(
+            "Cleared stacks: top +{}, middle +{}, bottom +{}",
+            rules.row_bonus(RowId::Top),
+            rules.row_bonus(RowId::Middle),
+            rules.row_bonus(RowId::Bottom)
+        ),
+    ]
+}
+
+pub fn draw_stats(r: &mut dyn Renderer, s: &Summary) {
+    r.print_xy(3, 1, "Statistics");
+    if s.games == 0 {
+        r.print_xy(3, 3, "No games finished yet.");
+        r.print_xy(3, 25, "Press any key to return");
+        return;
+    }
+    let secs = s.mean_duration.as_secs();
+    let lines = [
+        format!("Games played   {: >8}", s.games),
+        format!("Win rate       {: >7.0}%", s.win_rate() * 100.0),
+        format!("Average score  {: >8.1}", s.mean_score),
+        format!("Best score     {: >8}", s.best_score),
+        format!("Win streak     {: >8}", s.current_streak),
+        format!("Best streak    {: >8}", s.best_streak),
+        format!("Average time   {: >5}:{:02}", secs / 60, secs % 60),
+        format!("Trashes used   {: >8.1}", s.mean_trashes),
+    ];
+    r.print_xy(3, 3, &lines.join("\n"));
+    r.print_xy(40, 3, "Per game");
+    for (y, h) in (4..).zip(odds::HANDS.iter()) {
+        let n = s.hands.get(h).copied().unwrap_or(0.0);
+        r.print_xy(40, y, &format!("{: <20}{: >5.1}", h.to_string(), n));
+    }
+    let piles = ["Top", "Middle", "Bottom"];
+    for (y, (name, n)) in (12..).zip(piles.iter().zip(s.mean_piles.iter())) {
+        r.print_xy(
+            40,
+            y,
+            &format!("{} piles cleared{: >w$.1}", name, n, w = 11 - name.len()),
+        );
+    }
+
+    r.print_xy(3, 13, "Scores");
+    let most = s
+        .histogram
+        .iter()
+        .map(|(_, n)| *n)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    for (y, (low, n)) in (14..).zip(s.histogram.iter()) {
+        let bar = "█".repeat((n * 30).div_ceil(most));
+        let label = format!("{}-{}", low, low + s.bucket_width - 1);
+        r.print_xy(3, y, &format!("{: >7}  {} {}", label, bar, n));
+    }
+    r.print_xy(3, 25, "Press any key to return");
 }
@@ -0,0 +1,460 @@
+/// Replays: the moves of a game written out as plain text, so they can be
+/// stepped through later.
+///
+/// A replay file is the seed of the deal (or `deal` and the deal in
+/// `notation` form) followed by one move per line:
+///
+/// ```text
+/// # Auto Sage replay
+/// seed 42
+/// pair tl bl +2
+/// trash tc +0
+/// undo -2
+/// ```
+///
+/// The score change after each move is optional; when it's there, it's
+/// checked against what the move scores when it's replayed. A game played
+/// under other rules than the standard ones has a `rules` line, like
+/// `rules no-trash`, right after the seed.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::headless::parse_position;
+use crate::model::*;
+use crate::rules::Ruleset;
+use crate::update::update;
+
+pub const REPLAY_DIR: &str = "replays";
+
+const HEADER: &str = "# Auto Sage replay";
+
+/// How each hand is written in a replay file.
+const HAND_NAMES: [(Hand, &str); 8] = [
+    (Hand::Pair, "pair"),
+    (Hand::StraightThree, "straight3"),
+    (Hand::ThreeOfAKind, "three"),
+    (Hand::StraightFive, "straight5"),
+    (Hand::FullHouse, "fullhouse"),
+    (Hand::Flush, "flush"),
+    (Hand::FourOfAKind, "four"),
+    (Hand::StraightFlush, "straightflush"),
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Step {
+    pub effect: Effect,
+    pub score_delta: Option<i32>,
+}
+
+/// Where a replay's game was dealt from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Start {
+    Seed(u64),
+    Notation(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Replay {
+    pub start: Start,
+    pub rules: Ruleset,
+    pub steps: Vec<Step>,
+}
+
+/// The first step of a replay that can't be made on its deal.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    /// Counting from 1.
+    pub step: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Step {} doesn't match the deal: {}",
+            self.step, self.reason
+        )
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.effect {
+            Effect::Played(Move::Trash(pos), _) => write!(f, "Trash {}", pos)?,
+            Effect::Played(Move::PlayHand(h), positions) => {
+                let names: Vec<String> = positions.iter().map(|p| p.to_string()).collect();
+                write!(f, "{} ({})", h, names.join(" "))?
+            }
+            Effect::Undone => write!(f, "Undo")?,
+            Effect::Redone => write!(f, "Redo")?,
+        }
+        match self.score_delta {
+            Some(delta) => write!(f, " {:+}", delta),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Replay {
+    /// The moves made in a game so far. Messages that didn't change the
+    /// board, like selecting a stack, are left out.
+    pub fn of(game: &Game) -> Replay {
+        let steps = game
+            .log()
+            .iter()
+            .filter_map(|entry| {
+                entry.effect.clone().map(|effect| Step {
+                    effect,
+                    score_delta: Some(entry.score_delta),
+                })
+            })
+            .collect();
+        let start = if game.is_custom_deal() {
+            Start::Notation(game.to_notation())
+        } else {
+            Start::Seed(game.seed())
+        };
+        Replay {
+            start,
+            rules: game.rules().clone(),
+            steps,
+        }
+    }
+
+    /// The game after the first `n` steps, or as far as it got before the
+    /// replay stopped matching the deal.
+    pub fn game_at(&self, n: usize) -> (Game, Option<Divergence>) {
+        let mut g = match &self.start {
+            Start::Seed(seed) => Game::generate_with_seed(*seed),
+            Start::Notation(src) => {
+                Game::from_notation(src).expect("Replays check their deal when they're read")
+            }
+        }
+        .with_rules(self.rules.clone())
+        .expect("Replays only use preset rules");
+        for (idx, step) in self.steps.iter().take(n).enumerate() {
+            if let Err(reason) = make_step(&mut g, step) {
+                let divergence = Divergence {
+                    step: idx + 1,
+                    reason,
+                };
+                return (g, Some(divergence));
+            }
+        }
+        (g, None)
+    }
+
+    /// Where the replay stops matching its deal, if it does.
+    pub fn check(&self) -> Option<Divergence> {
+        self.game_at(self.steps.len()).1
+    }
+}
+
+/// Make one step of a replay, or say why it can't be made.
+fn make_step(g: &mut Game, step: &Step) -> Result<(), String> {
+    let msg = match &step.effect {
+        Effect::Played(mv, positions) => {
+            if g.status() != Status::InProgress {
+                return Err("the game is already over".to_owned());
+            }
+            if let Some(pos) = positions
+                .iter()
+                .find(|p| g.spread.get_stack(**p).is_empty())
+            {
+                return Err(format!("{} is empty", pos));
+            }
+            let selected = positions.iter().copied().collect();
+            match mv {
+                Move::Trash(_) if g.trashes == Trashes::None => {
+                    return Err("there are no trashes left".to_owned())
+                }
+                Move::PlayHand(h) if g.hand_at(&selected) != Some(*h) => {
+                    let names: Vec<String> = positions.iter().map(|p| p.to_string()).collect();
+                    return Err(format!("{} isn't a {}", names.join(" "), h));
+                }
+                _ => g.selected = selected,
+            }
+            Msg::MakeMove
+        }
+        Effect::Undone => Msg::Undo,
+        Effect::Redone => Msg::Redo,
+    };
+    if update(msg, g).is_err() {
+        return Err(match msg {
+            Msg::Undo => "there's nothing to undo".to_owned(),
+            Msg::Redo => "there's nothing to redo".to_owned(),
+            _ => "that move can't be made".to_owned(),
+        });
+    }
+    let entry = g.log().last().expect("update logs every move it makes");
+    match step.score_delta {
+        Some(expected) if expected != entry.score_delta => Err(format!(
+            "it scored {:+}, not {:+}",
+            entry.score_delta, expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        match &self.start {
+            Start::Seed(seed) => writeln!(f, "seed {}", seed)?,
+            Start::Notation(src) => writeln!(f, "deal {}", src)?,
+        }
+        if !self.rules.is_standard() {
+            writeln!(f, "rules {}", self.rules.name)?;
+        }
+        for step in self.steps.iter() {
+            match &step.effect {
+                Effect::Played(Move::Trash(pos), _) => write!(f, "trash {}", pos)?,
+                Effect::Played(Move::PlayHand(h), positions) => {
+                    let (_, name) = HAND_NAMES.iter().find(|(hand, _)| hand == h).unwrap();
+                    write!(f, "{}", name)?;
+                    for pos in positions {
+                        write!(f, " {}", pos)?;
+                    }
+                }
+                Effect::Undone => write!(f, "undo")?,
+                Effect::Redone => write!(f, "redo")?,
+            }
+            match step.score_delta {
+                Some(delta) => writeln!(f, " {:+}", delta)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_step(line: &str) -> Result<Step, String> {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    let score_delta = match words.last() {
+        Some(w) if w.starts_with('+') || w.starts_with('-') => {
+            let delta = w
+                .parse::<i32>()
+                .map_err(|_| format!("'{}' isn't a score", w))?;
+            words.pop();
+            Some(delta)
+        }
+        _ => None,
+    };
+    let (name, rest) = words.split_first().ok_or("missing move")?;
+    let mut positions = Vec::new();
+    for w in rest {
+        match parse_position(w) {
+            Some(p) => positions.push(p),
+            None => return Err(format!("'{}' isn't a stack", w)),
+        }
+    }
+    let effect = match (*name, positions.as_slice()) {
+        ("undo", []) => Effect::Undone,
+        ("redo", []) => Effect::Redone,
+        ("trash", [pos]) => Effect::Played(Move::Trash(*pos), positions.clone()),
+        ("undo", _) | ("redo", _) => return Err(format!("'{}' doesn't take any stacks", name)),
+        ("trash", _) => return Err("trash takes exactly one stack".to_owned()),
+        _ => match HAND_NAMES.iter().find(|(_, n)| n == name) {
+            Some((h, _)) => {
+                // Keep the stacks in the same order the log does
+                let ordered = PILE_SIZES
+                    .iter()
+                    .map(|(pos, _)| *pos)
+                    .filter(|pos| positions.contains(pos))
+                    .collect();
+                Effect::Played(Move::PlayHand(*h), ordered)
+            }
+            None => return Err(format!("unknown move '{}'", name)),
+        },
+    };
+    Ok(Step {
+        effect,
+        score_delta,
+    })
+}
+
+fn parse_start(line: &str) -> Result<Start, String> {
+    if let Some(src) = line.strip_prefix("deal ") {
+        return match Game::from_notation(src) {
+            Ok(_) => Ok(Start::Notation(src.trim().to_owned())),
+            Err(e) => Err(e.to_string()),
+        };
+    }
+    match line.strip_prefix("seed ").map(|s| s.trim().parse::<u64>()) {
+        Some(Ok(seed)) => Ok(Start::Seed(seed)),
+        _ => Err("expected 'seed N' or 'deal ...'".to_owned()),
+    }
+}
+
+/// Read a replay, with the line number of the first problem if it can't be.
+pub fn parse(src: &str) -> Result<Replay, String> {
+    let mut start = None;
+    let mut rules = Ruleset::standard();
+    let mut steps = Vec::new();
+    for (n, line) in (1..).zip(src.lines()) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if start.is_none() {
+            start = Some(parse_start(line).map_err(|e| format!("line {}: {}", n, e))?);
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("rules ").filter(|_| steps.is_empty()) {
+            rules = Ruleset::preset(name.trim())
+                .ok_or_else(|| format!("line {}: unknown rules '{}'", n, name.trim()))?;
+            continue;
+        }
+        steps.push(parse_step(line).map_err(|e| format!("line {}: {}", n, e))?);
+    }
+    match start {
+        Some(start) => Ok(Replay {
+            start,
+            rules,
+            steps,
+        }),
+        None => Err("no seed given".to_owned()),
+    }
+}
+
+pub fn load(path: &Path) -> Result<Replay, String> {
+    let src =
+        fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    parse(&src).map_err(|e| format!("{} isn't a replay: {}", path.display(), e))
+}
+
+pub fn save(game: &Game, path: &Path) -> io::Result<()> {
+    fs::write(path, Replay::of(game).to_string())
+}
+
+/// Save a game's replay under `REPLAY_DIR`, named after its seed, without
+/// overwriting an earlier one.
+pub fn export(game: &Game) -> io::Result<PathBuf> {
+    fs::create_dir_all(REPLAY_DIR)?;
+    let path = (1..)
+        .map(|n| {
+            let name = if game.is_custom_deal() {
+                format!("custom-{}.txt", n)
+            } else {
+                format!("{}-{}.txt", game.seed(), n)
+            };
+            Path::new(REPLAY_DIR).join(name)
+        })
+        .find(|p| !p.exists())
+        .unwrap();
+    save(game, &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+fn play(g: &mut Game, positions: &[Position]) {
+    g.selected = positions.iter().copied().collect();
+    update(Msg::MakeMove, g).unwrap();
+}
+
+#[test]
+fn test_round_trip() {
+    let mut g = Game::generate_with_seed(3);
+    let (_, first) = g.legal_moves().into_iter().next().unwrap();
+    let first: Vec<Position> = first.into_iter().collect();
+    play(&mut g, &first);
+    update(Msg::Undo, &mut g).unwrap();
+    update(Msg::Hint, &mut g).unwrap();
+    update(Msg::Redo, &mut g).unwrap();
+    let (_, second) = g.legal_moves().into_iter().last().unwrap();
+    let second: Vec<Position> = second.into_iter().collect();
+    play(&mut g, &second);
+
+    let replay = parse(&Replay::of(&g).to_string()).unwrap();
+    assert_eq!(replay, Replay::of(&g));
+    assert_eq!(replay.steps.len(), 4);
+    assert_eq!(replay.steps[1].effect, Effect::Undone);
+    assert_eq!(replay.check(), None);
+    let (end, _) = replay.game_at(4);
+    let (after_undo, _) = replay.game_at(2);
+    let fresh = Game::generate_with_seed(3);
+    for (pos, _) in PILE_SIZES.iter() {
+        assert_eq!(end.spread.get_stack(*pos), g.spread.get_stack(*pos));
+        assert_eq!(
+            after_undo.spread.get_stack(*pos),
+            fresh.spread.get_stack(*pos)
+        );
+    }
+    assert_eq!(end.score(), g.score());
+
+    let custom = Game::from_notation(&g.to_notation()).unwrap();
+    let replay = parse(&Replay::of(&custom).to_string()).unwrap();
+    assert_eq!(replay.start, Start::Notation(g.to_notation()));
+}
+
+#[test]
+fn test_divergence() {
+    let g = Game::generate_with_seed(3);
+    let tl = Position(RowId::Top, ColumnId::Left);
+    // Hands always need cards from two rows
+    let replay = parse("seed 3\ntrash tl -0\npair tl tc\n").unwrap();
+    assert_eq!(
+        replay.steps[0].effect,
+        Effect::Played(Move::Trash(tl), vec![tl])
+    );
+    let divergence = replay.check().unwrap();
+    assert_eq!(divergence.step, 2);
+    assert_eq!(divergence.reason, "tl tc isn't a Pair");
+    let (stopped, _) = replay.game_at(2);
+    assert_eq!(stopped.spread.tl.len(), g.spread.tl.len() - 1);
+    assert!(stopped.selected.is_empty());
+
+    let wrong_score = parse("seed 3\ntrash tl +4\n").unwrap();
+    assert_eq!(wrong_score.check().unwrap().reason, "it scored +0, not +4");
+    let early_redo = parse("seed 3\nredo\n").unwrap();
+    assert_eq!(
+        early_redo.check(),
+        Some(Divergence {
+            step: 1,
+            reason: "there's nothing to redo".to_owned()
+        })
+    );
+    let too_many = parse("seed 3\ntrash tl\ntrash tl\ntrash tl\n").unwrap();
+    assert_eq!(too_many.check().unwrap().step, 3);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        parse("# Auto Sage replay\nseed x\n"),
+        Err("line 2: expected 'seed N' or 'deal ...'".to_owned())
+    );
+    assert_eq!(
+        parse("seed 1\npair tl\njump tl\n"),
+        Err("line 3: unknown move 'jump'".to_owned())
+    );
+    assert!(parse("seed 1\ntrash tl tc\n").is_err());
+    assert!(parse("seed 1\npair tl zz\n").is_err());
+    assert!(parse("seed 1\nundo +x\n").is_err());
+    assert!(parse("deal 2h/3h\n")
+        .unwrap_err()
+        .starts_with("line 1: A deal has"));
+    assert!(parse("").is_err());
+}
+
+#[test]
+fn test_rules() {
+    let g = Game::generate_with_seed(3)
+        .with_rules(Ruleset::no_trash())
+        .unwrap();
+    let src = Replay::of(&g).to_string();
+    assert!(src.contains("\nrules no-trash\n"));
+    let replay = parse(&format!("{}trash tl\n", src)).unwrap();
+    assert_eq!(replay.rules, Ruleset::no_trash());
+    assert_eq!(
+        replay.check().map(|d| d.reason),
+        Some("there are no trashes left".to_owned())
+    );
+    assert_eq!(
+        parse("seed 3\nrules easy\n"),
+        Err("line 2: unknown rules 'easy'".to_owned())
+    );
+}
@@ -0,0 +1,186 @@
+/// Saving games to disk and loading them back.
+///
+/// Saves are JSON files with a format version alongside the game, so a file
+/// from an incompatible version of Auto Sage is reported instead of being
+/// misread.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Game;
+
+/// Bump this whenever a change to `Game` makes old saves unreadable.
+pub const SAVE_VERSION: u32 = 1;
+
+pub const SAVE_DIR: &str = "saves";
+pub const SLOTS: usize = 5;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(PathBuf, io::Error),
+    Corrupt(PathBuf, String),
+    WrongVersion(PathBuf, u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(path, e) => write!(f, "Couldn't access {}: {}", path.display(), e),
+            SaveError::Corrupt(path, e) => write!(f, "{} is corrupt: {}", path.display(), e),
+            SaveError::WrongVersion(path, v) => write!(
+                f,
+                "{} is save format {}, but this version of Auto Sage reads format {}",
+                path.display(),
+                v,
+                SAVE_VERSION
+            ),
+        }
+    }
+}
+
+/// Where a game is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    /// Written when the game is closed and read when it's opened again.
+    Auto,
+    /// One of the numbered slots, from 1 to `SLOTS`.
+    Numbered(usize),
+}
+
+impl Slot {
+    pub fn path(self) -> PathBuf {
+        let name = match self {
+            Slot::Auto => "autosave.json".to_owned(),
+            Slot::Numbered(n) => format!("slot{}.json", n),
+        };
+        [SAVE_DIR, &name].iter().collect()
+    }
+}
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+/// The rest of a save file, once its version is known to be right.
+#[derive(Deserialize)]
+struct SaveFile {
+    game: Game,
+}
+
+/// Just enough of a save file to check its version before reading the rest.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub fn save(game: &Game, slot: Slot) -> Result<(), SaveError> {
+    let path = slot.path();
+    fs::create_dir_all(SAVE_DIR).map_err(|e| SaveError::Io(path.clone(), e))?;
+    fs::write(&path, to_string(game)).map_err(|e| SaveError::Io(path, e))
+}
+
+/// Load a saved game. `Ok(None)` means there's nothing in the slot.
+pub fn load(slot: Slot) -> Result<Option<Game>, SaveError> {
+    let path = slot.path();
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(SaveError::Io(path, e)),
+    };
+    from_str(&src).map(Some).map_err(|e| match e {
+        LoadError::Corrupt(msg) => SaveError::Corrupt(path, msg),
+        LoadError::WrongVersion(v) => SaveError::WrongVersion(path, v),
+    })
+}
+
+enum LoadError {
+    Corrupt(String),
+    WrongVersion(u32),
+}
+
+fn to_string(game: &Game) -> String {
+    let file = SaveFileRef {
+        version: SAVE_VERSION,
+        game,
+    };
+    serde_json::to_string_pretty(&file).expect("Games can always be serialized")
+}
+
+fn from_str(src: &str) -> Result<Game, LoadError> {
+    let header: SaveHeader =
+        serde_json::from_str(src).map_err(|e| LoadError::Corrupt(e.to_string()))?;
+    if header.version != SAVE_VERSION {
+        return Err(LoadError::WrongVersion(header.version));
+    }
+    let file: SaveFile =
+        serde_json::from_str(src).map_err(|e| LoadError::Corrupt(e.to_string()))?;
+    let game = file.game;
+    if game
+        .selected
+        .iter()
+        .any(|p| game.spread.get_stack(*p).is_empty())
+    {
+        return Err(LoadError::Corrupt("an empty stack is selected".to_owned()));
+    }
+    game.check_deal()
+        .map_err(|e| LoadError::Corrupt(e.to_string()))?;
+    Ok(game)
+}
+
+#[test]
+fn test_round_trip() {
+    use crate::model::{ColumnId, Msg, Position, RowId};
+    let mut g = Game::generate_with_seed(99);
+    crate::update::update(
+        Msg::ToggleStack(Position(RowId::Top, ColumnId::Left)),
+        &mut g,
+    )
+    .unwrap();
+    crate::update::update(Msg::MakeMove, &mut g).unwrap();
+    crate::update::update(
+        Msg::ToggleStack(Position(RowId::Middle, ColumnId::Left)),
+        &mut g,
+    )
+    .unwrap();
+
+    let loaded = match from_str(&to_string(&g)) {
+        Ok(loaded) => loaded,
+        Err(_) => panic!("Couldn't read back a saved game"),
+    };
+    assert_eq!(loaded.spread.tl, g.spread.tl);
+    assert_eq!(loaded.selected, g.selected);
+    assert_eq!(loaded.trashes, g.trashes);
+    assert_eq!(loaded.bonus_card, g.bonus_card);
+    assert_eq!(loaded.seed(), 99);
+
+    let mut loaded = loaded;
+    assert_eq!(loaded.undo(), Ok(()), "History should survive saving");
+    assert_eq!(loaded.spread.tl.len(), 8);
+}
+
+#[test]
+fn test_bad_files() {
+    assert!(matches!(
+        from_str("{\"version\": 0, \"game\": {}}"),
+        Err(LoadError::WrongVersion(0))
+    ));
+    assert!(matches!(from_str("not json"), Err(LoadError::Corrupt(_))));
+    assert!(matches!(
+        from_str("{\"version\": 1, \"game\": {\"spread\": 3}}"),
+        Err(LoadError::Corrupt(_))
+    ));
+
+    // Rules that can't deal the game's seed
+    let mut edited: serde_json::Value =
+        serde_json::from_str(&to_string(&Game::generate_with_seed(3))).unwrap();
+    edited["game"]["rules"]["pile_sizes"][0] = 20.into();
+    assert!(matches!(
+        from_str(&edited.to_string()),
+        Err(LoadError::Corrupt(msg)) if msg.contains("deal")
+    ));
+}
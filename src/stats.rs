@@ -0,0 +1,332 @@
+/// Statistics kept across games.
+///
+/// Every finished game is added to a JSON file next to the saves, and the
+/// stats screen summarises them.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::*;
+use crate::rules::Ruleset;
+use crate::saves::{SaveError, SAVE_DIR};
+
+const STATS_VERSION: u32 = 1;
+
+/// Scores are grouped into at most this many bars on the histogram.
+const MAX_BUCKETS: u32 = 8;
+
+/// How one game went.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub score: u32,
+    pub won: bool,
+    pub hands: BTreeMap<Hand, u32>,
+    pub trashes_used: u32,
+    /// Top, middle and bottom.
+    pub piles_cleared: [u32; 3],
+    pub duration: Duration,
+    /// What the game was played under. Records from before this was kept
+    /// were all standard games.
+    #[serde(default)]
+    pub rules: Ruleset,
+}
+
+impl GameRecord {
+    pub fn of(game: &Game, duration: Duration) -> GameRecord {
+        let mut hands = BTreeMap::new();
+        let mut trashes_used = 0;
+        for mv in game.moves_made() {
+            match mv {
+                Move::PlayHand(h) => *hands.entry(h).or_insert(0) += 1,
+                Move::Trash(_) => trashes_used += 1,
+            }
+        }
+        let mut piles_cleared = [0; 3];
+        for (pos @ Position(rowid, _), _) in PILE_SIZES.iter() {
+            if game.spread.get_stack(*pos).is_empty() {
+                piles_cleared[*rowid as usize] += 1;
+            }
+        }
+        GameRecord {
+            seed: game.seed(),
+            score: game.score(),
+            won: game.status() == Status::Won,
+            hands,
+            trashes_used,
+            piles_cleared,
+            duration,
+            rules: game.rules().clone(),
+        }
+    }
+}
+
+/// Times the current game and notices when it finishes, so each game is
+/// recorded once.
+pub struct Tracker {
+    started: Instant,
+    recorded: bool,
+}
+
+impl Tracker {
+    /// Start timing `game`. A game that's already over (say, one just
+    /// loaded) isn't recorded again.
+    pub fn new(game: &Game) -> Self {
+        Tracker {
+            started: Instant::now(),
+            recorded: game.status() != Status::InProgress,
+        }
+    }
+
+    /// Call after every move. Returns the game's record the first time it's
+    /// over.
+    pub fn observe(&mut self, game: &Game) -> Option<GameRecord> {
+        if self.recorded || game.status() == Status::InProgress {
+            return None;
+        }
+        self.recorded = true;
+        Some(GameRecord::of(game, self.started.elapsed()))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatsFile {
+    version: u32,
+    games: Vec<GameRecord>,
+}
+
+fn path() -> PathBuf {
+    [SAVE_DIR, "stats.json"].iter().collect()
+}
+
+/// Every game recorded so far, oldest first.
+pub fn load() -> Result<Vec<GameRecord>, SaveError> {
+    let path = path();
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(SaveError::Io(path, e)),
+    };
+    let file: StatsFile =
+        serde_json::from_str(&src).map_err(|e| SaveError::Corrupt(path.clone(), e.to_string()))?;
+    if file.version != STATS_VERSION {
+        return Err(SaveError::Corrupt(
+            path,
+            format!("unknown stats version {}", file.version),
+        ));
+    }
+    Ok(file.games)
+}
+
+pub fn record(game: GameRecord) -> Result<(), SaveError> {
+    let mut games = load()?;
+    games.push(game);
+    let path = path();
+    let file = StatsFile {
+        version: STATS_VERSION,
+        games,
+    };
+    let src = serde_json::to_string(&file).expect("Stats can always be serialized");
+    fs::create_dir_all(SAVE_DIR).map_err(|e| SaveError::Io(path.clone(), e))?;
+    fs::write(&path, src).map_err(|e| SaveError::Io(path, e))
+}
+
+/// What the stats screen shows.
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub games: usize,
+    pub wins: usize,
+    pub mean_score: f64,
+    pub best_score: u32,
+    /// Average number of each hand played per game.
+    pub hands: BTreeMap<Hand, f64>,
+    pub mean_trashes: f64,
+    pub mean_piles: [f64; 3],
+    pub mean_duration: Duration,
+    /// Wins in a row, counting back from the last game.
+    pub current_streak: usize,
+    pub best_streak: usize,
+    /// The lowest score in each bucket and how many games fell in it.
+    pub histogram: Vec<(u32, usize)>,
+    pub bucket_width: u32,
+}
+
+impl Summary {
+    pub fn of(games: &[GameRecord]) -> Summary {
+        let n = games.len().max(1) as f64;
+        let mean = |f: &dyn Fn(&GameRecord) -> u32| games.iter().map(f).sum::<u32>() as f64 / n;
+
+        let mut hands = BTreeMap::new();
+        for g in games {
+            for (h, count) in g.hands.iter() {
+                *hands.entry(*h).or_insert(0.0) += *count as f64 / n;
+            }
+        }
+
+        let mut best_streak = 0;
+        let mut current_streak = 0;
+        for g in games {
+            current_streak = if g.won { current_streak + 1 } else { 0 };
+            best_streak = best_streak.max(current_streak);
+        }
+
+        let best_score = games.iter().map(|g| g.score).max().unwrap_or(0);
+        // Round bucket widths up to a multiple of five so the labels are tidy
+        let bucket_width = ((best_score / MAX_BUCKETS) / 5 + 1) * 5;
+        let mut histogram: Vec<(u32, usize)> = (0..=best_score / bucket_width)
+            .map(|idx| (idx * bucket_width, 0))
+            .collect();
+        for g in games {
+            histogram[(g.score / bucket_width) as usize].1 += 1;
+        }
+
+        let total: Duration = games.iter().map(|g| g.duration).sum();
+        Summary {
+            games: games.len(),
+            wins: games.iter().filter(|g| g.won).count(),
+            mean_score: mean(&|g| g.score),
+            best_score,
+            hands,
+            mean_trashes: mean(&|g| g.trashes_used),
+            mean_piles: [
+                mean(&|g| g.piles_cleared[0]),
+                mean(&|g| g.piles_cleared[1]),
+                mean(&|g| g.piles_cleared[2]),
+            ],
+            mean_duration: total / games.len().max(1) as u32,
+            current_streak,
+            best_streak,
+            histogram,
+            bucket_width,
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+fn game(score: u32, won: bool) -> GameRecord {
+    GameRecord {
+        seed: 0,
+        score,
+        won,
+        hands: [(Hand::Pair, 2)].iter().copied().collect(),
+        trashes_used: 1,
+        piles_cleared: [0, 1, 3],
+        duration: Duration::from_secs(60),
+        rules: Ruleset::standard(),
+    }
+}
+
+#[test]
+fn test_summary() {
+    let games = vec![
+        game(10, false),
+        game(80, true),
+        game(90, true),
+        game(12, false),
+        game(85, true),
+    ];
+    let s = Summary::of(&games);
+    assert_eq!(s.games, 5);
+    assert_eq!(s.wins, 3);
+    assert!((s.win_rate() - 0.6).abs() < 1e-9);
+    assert_eq!(s.best_score, 90);
+    assert!((s.mean_score - 55.4).abs() < 1e-9);
+    assert_eq!(s.current_streak, 1);
+    assert_eq!(s.best_streak, 2);
+    assert_eq!(s.hands[&Hand::Pair], 2.0);
+    assert_eq!(s.mean_piles, [0.0, 1.0, 3.0]);
+    assert_eq!(s.mean_duration, Duration::from_secs(60));
+    assert_eq!(s.bucket_width, 15);
+    assert_eq!(
+        s.histogram,
+        vec![(0, 2), (15, 0), (30, 0), (45, 0), (60, 0), (75, 2), (90, 1)]
+    );
+
+    let empty = Summary::of(&[]);
+    assert_eq!(empty.games, 0);
+    assert_eq!(empty.histogram, vec![(0, 0)]);
+}
+
+#[test]
+fn test_record_of_game() {
+    use crate::update::update;
+    use autosage_engine::notation;
+    let mut g = Game::generate_with_seed(4);
+    let mut tracker = Tracker::new(&g);
+    g.spread = notation::parse_spread("5c//////5h//").unwrap();
+    g.selected = [Position(RowId::Top, ColumnId::Left)]
+        .iter()
+        .copied()
+        .collect();
+    update(Msg::MakeMove, &mut g).unwrap();
+    update(Msg::Undo, &mut g).unwrap();
+    assert_eq!(tracker.observe(&g), None);
+    g.selected = [
+        Position(RowId::Top, ColumnId::Left),
+        Position(RowId::Bottom, ColumnId::Left),
+    ]
+    .iter()
+    .copied()
+    .collect();
+    update(Msg::MakeMove, &mut g).unwrap();
+    let record = tracker.observe(&g).unwrap();
+    assert_eq!(record.hands, [(Hand::Pair, 1)].iter().copied().collect());
+    assert!(record.won);
+    // Every stack in this little deal ends up empty
+    assert_eq!(record.piles_cleared, [3, 3, 3]);
+    assert_eq!(record.trashes_used, 0);
+    assert_eq!(record.seed, 4);
+    assert_eq!(tracker.observe(&g), None);
+    update(Msg::RestartDeal, &mut g).unwrap();
+    assert!(Tracker::new(&g).observe(&g).is_none());
+}
+
+#[test]
+fn test_record_counts_every_trash() {
+    use crate::update::update;
+    use autosage_engine::notation;
+    let mut g = Game::generate_with_seed(4);
+    g.spread = notation::parse_spread("2h5c9d/3s/4c//5h////").unwrap();
+    let play = |g: &mut Game, stacks: &[&str]| {
+        g.selected = PILE_SIZES
+            .iter()
+            .map(|(pos, _)| *pos)
+            .filter(|pos| stacks.contains(&pos.to_string().as_str()))
+            .collect();
+        update(Msg::MakeMove, g).unwrap();
+    };
+    // Trash, trash, a pair that gives a trash back, then trash again
+    play(&mut g, &["tl"]);
+    play(&mut g, &["tc"]);
+    assert_eq!(g.trashes, Trashes::None);
+    play(&mut g, &["tl", "mc"]);
+    assert_eq!(g.trashes, Trashes::One);
+    play(&mut g, &["tr"]);
+    let record = GameRecord::of(&g, Duration::from_secs(1));
+    assert_eq!(record.trashes_used, 3);
+    assert_eq!(record.hands, [(Hand::Pair, 1)].iter().copied().collect());
+}
+
+#[test]
+fn test_record_keeps_rules() {
+    let g = Game::generate_with_seed(4)
+        .with_rules(Ruleset::no_trash())
+        .unwrap();
+    let record = GameRecord::of(&g, Duration::from_secs(1));
+    assert_eq!(record.trashes_used, 0);
+    assert_eq!(record.rules.name, "no-trash");
+
+    // Records written before the rules were kept read back as standard
+    let mut old = serde_json::to_value(&record).unwrap();
+    old.as_object_mut().unwrap().remove("rules");
+    let old: GameRecord = serde_json::from_value(old).unwrap();
+    assert!(old.rules.is_standard());
+}
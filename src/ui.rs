@@ -0,0 +1,283 @@
+/// The interactive game loop, shared by every terminal backend.
+use crate::config::Config;
+use crate::daily;
+use crate::input::{self, Event, Events, Key, KeyMap};
+use crate::layout::{self, Target};
+use crate::model::{Game, Msg};
+use crate::odds::{self, Odds};
+use crate::render::{self, Renderer};
+use crate::replay::{self, Replay};
+use crate::rules::Ruleset;
+use crate::saves;
+use crate::stats::{self, Summary, Tracker};
+use crate::update;
+
+/// Anything that can both show the game and take input.
+pub trait Terminal: Renderer + Events {}
+
+impl<T: Renderer + Events> Terminal for T {}
+
+/// Wait for a key press or a click, e.g. to leave a screen.
+fn wait_for_key<T: Terminal>(term: &mut T) {
+    loop {
+        match term.wait_event() {
+            Event::Redraw | Event::MouseMove(..) => continue,
+            _ => return,
+        }
+    }
+}
+
+/// Show the help screen until a key is pressed.
+fn show_help<T: Terminal>(term: &mut T, keys: &KeyMap, rules: &Ruleset) {
+    term.clear();
+    render::draw_help(term, keys, rules);
+    term.refresh();
+    wait_for_key(term);
+}
+
+/// Show the stats screen until a key is pressed.
+fn show_stats<T: Terminal>(term: &mut T) -> Result<(), saves::SaveError> {
+    let summary = Summary::of(&stats::load()?);
+    term.clear();
+    render::draw_stats(term, &summary);
+    term.refresh();
+    wait_for_key(term);
+    Ok(())
+}
+
+/// Show the save slots and wait for the player to pick one (or cancel with
+/// any other key).
+fn choose_slot<T: Terminal>(term: &mut T, title: &str) -> Option<saves::Slot> {
+    let slots: Vec<_> = (1..=saves::SLOTS)
+        .map(|n| saves::load(saves::Slot::Numbered(n)))
+        .collect();
+    term.clear();
+    render::draw_slot_menu(term, title, &slots);
+    term.refresh();
+    loop {
+        match term.wait_event() {
+            Event::Redraw | Event::MouseMove(..) => continue,
+            Event::Click(..) => return None,
+            Event::Key(k) => {
+                return input::parse_slot(k)
+                    .filter(|n| (1..=saves::SLOTS).contains(n))
+                    .map(saves::Slot::Numbered);
+            }
+            Event::Close => return None,
+        }
+    }
+}
+
+/// What the panel on the right shows.
+#[derive(Clone, Copy, PartialEq)]
+enum Panel {
+    Odds,
+    /// What could be under the selected stack.
+    Buried,
+    /// The cards taken off the spread, scrolled down this many.
+    Graveyard {
+        scroll: usize,
+    },
+}
+
+fn draw<T: Terminal>(
+    term: &mut T,
+    g: &Game,
+    odds: &Odds,
+    panel: Panel,
+    notice: &Option<String>,
+    keys: &KeyMap,
+    hover: Option<Target>,
+) {
+    term.clear();
+    render::draw_game(term, g, odds, keys, hover);
+    match panel {
+        Panel::Odds => render::draw_odds(term, odds),
+        Panel::Buried => render::draw_buried(term, g),
+        Panel::Graveyard { scroll } => render::draw_graveyard(term, g, scroll),
+    }
+    if let Some(msg) = notice {
+        render::draw_notice(term, g, msg);
+    }
+    term.refresh();
+}
+
+/// Play until the player quits. `notice` is shown until the first key press.
+pub fn run<T: Terminal>(term: &mut T, g: &mut Game, mut notice: Option<String>, cfg: &Config) {
+    let mut odds = odds::compute(g);
+    let mut panel = Panel::Odds;
+    let mut tracker = Tracker::new(g);
+    let mut hover = None;
+    draw(term, g, &odds, panel, &notice, &cfg.keys, hover);
+    loop {
+        let msg = match term.wait_event() {
+            Event::Close => break, // leave main loop on quit
+            Event::Redraw => None,
+            Event::MouseMove(x, y) => {
+                let pointed = layout::target_at(x, y);
+                if pointed == hover {
+                    continue;
+                }
+                hover = pointed;
+                None
+            }
+            Event::Click(x, y) => {
+                notice = None;
+                layout::target_at(x, y).map(|target| match target {
+                    Target::Stack(pos) => Msg::ToggleStack(pos),
+                    Target::Move => Msg::MakeMove,
+                })
+            }
+            Event::Key(k) => {
+                notice = None;
+                match k {
+                    Key::Escape => break,
+                    Key::Char('h') => {
+                        show_help(term, &cfg.keys, g.rules());
+                        None
+                    }
+                    Key::Char('t') => {
+                        if let Err(e) = show_stats(term) {
+                            notice = Some(e.to_string());
+                        }
+                        None
+                    }
+                    Key::Char('b') => {
+                        panel = match panel {
+                            Panel::Buried => Panel::Odds,
+                            _ => Panel::Buried,
+                        };
+                        None
+                    }
+                    Key::Char('g') => {
+                        panel = match panel {
+                            Panel::Graveyard { .. } => Panel::Odds,
+                            _ => Panel::Graveyard { scroll: 0 },
+                        };
+                        None
+                    }
+                    // Keys bound to an action do that instead
+                    Key::Up | Key::Down
+                        if matches!(panel, Panel::Graveyard { .. })
+                            && cfg.keys.msg(k).is_none() =>
+                    {
+                        let last = g.discards().len().saturating_sub(render::GRAVEYARD_ROWS);
+                        if let Panel::Graveyard { scroll } = &mut panel {
+                            *scroll = match k {
+                                Key::Up => scroll.saturating_sub(1),
+                                _ => (*scroll + 1).min(last),
+                            };
+                        }
+                        None
+                    }
+                    Key::F(5) => {
+                        if let Some(slot) = choose_slot(term, "Save to which slot?") {
+                            notice = Some(match saves::save(g, slot) {
+                                Ok(()) => "Game saved.".to_owned(),
+                                Err(e) => e.to_string(),
+                            });
+                        }
+                        None
+                    }
+                    Key::F(6) => {
+                        notice = Some(match replay::export(g) {
+                            Ok(path) => format!("Replay saved to {}.", path.display()),
+                            Err(e) => format!("Couldn't save the replay: {}", e),
+                        });
+                        None
+                    }
+                    Key::F(9) => {
+                        if let Some(slot) = choose_slot(term, "Load which slot?") {
+                            notice = Some(match saves::load(slot) {
+                                Ok(Some(loaded)) => {
+                                    *g = loaded;
+                                    g.set_undo_limit(cfg.undo_limit);
+                                    odds = odds::compute(g);
+                                    tracker = Tracker::new(g);
+                                    "Game loaded.".to_owned()
+                                }
+                                Ok(None) => "That slot is empty.".to_owned(),
+                                Err(e) => e.to_string(),
+                            });
+                        }
+                        None
+                    }
+                    // If a key was pressed that matches an input method, update the game
+                    _ => cfg.keys.msg(k),
+                }
+            }
+        };
+        if let Some(msg) = msg {
+            if let Err(e) = update::update(msg, g) {
+                notice = Some(e.to_string());
+            }
+            odds = odds::compute(g);
+            if let Msg::NewGame | Msg::RestartDeal = msg {
+                tracker = Tracker::new(g);
+            }
+            if let Some(record) = tracker.observe(g) {
+                if let Err(e) = stats::record(record) {
+                    notice = Some(e.to_string());
+                }
+                match daily::finish(g) {
+                    Ok(Some((_, path))) => {
+                        notice = Some(format!(
+                            "Daily deal done! A summary to share is in {}.",
+                            path.display()
+                        ))
+                    }
+                    Ok(None) => (),
+                    Err(e) => notice = Some(e.to_string()),
+                }
+            }
+        }
+        draw(term, g, &odds, panel, &notice, &cfg.keys, hover);
+    }
+}
+
+/// Step through a replay: Right or Space goes forward, Left or Backspace goes
+/// back, and Esc leaves. A replay that stops matching its deal can only be
+/// played up to that point.
+pub fn show_replay<T: Terminal>(term: &mut T, replay: &Replay, keys: &KeyMap) {
+    let divergence = replay.check();
+    let last = match &divergence {
+        Some(d) => d.step - 1,
+        None => replay.steps.len(),
+    };
+    let mut step = 0;
+    loop {
+        let (g, _) = replay.game_at(step);
+        let mut msg = match step {
+            0 => format!("Start of {} moves.", replay.steps.len()),
+            _ => format!(
+                "Move {} of {}: {}.",
+                step,
+                replay.steps.len(),
+                replay.steps[step - 1]
+            ),
+        };
+        if let (Some(d), true) = (&divergence, step == last) {
+            msg.push_str(&format!(" {}.", d));
+        }
+        msg.push_str(" ←/→ to step, Esc to leave.");
+        draw(
+            term,
+            &g,
+            &odds::compute(&g),
+            Panel::Odds,
+            &Some(msg),
+            keys,
+            None,
+        );
+        loop {
+            match term.wait_event() {
+                Event::Close | Event::Key(Key::Escape) => return,
+                Event::Key(Key::Right) | Event::Key(Key::Space) if step < last => step += 1,
+                Event::Key(Key::Left) | Event::Key(Key::Backspace) if step > 0 => step -= 1,
+                Event::Redraw => (),
+                Event::Key(_) | Event::Click(..) | Event::MouseMove(..) => continue,
+            }
+            break;
+        }
+    }
+}